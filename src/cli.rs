@@ -0,0 +1,194 @@
+use crate::crawler::{DoclnCrawler, NovelCategory, OutputFormat};
+use crate::error::{DoclnError, Result};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use log::error;
+
+/// 命令行参数
+#[derive(Debug, Clone)]
+pub struct CliArgs {
+    pub category: NovelCategory,
+    pub novel_ids: Vec<u32>,
+    pub dry_run: bool, // 只列出章节，不下载
+    pub output_formats: Vec<OutputFormat>,
+    pub per_volume: bool, // 每卷生成一个EPUB
+    pub base_url: Option<String>, // 站点镜像地址，默认使用docln.net
+    pub incremental: bool, // 只下载与上次状态相比新增或变化的章节
+    pub respect_robots: bool, // 遵守站点的robots.txt
+    pub rtl: bool, // EPUB从右到左翻页
+    pub merge_parts: bool, // 合并分段章节
+    pub list_volumes: bool, // 只列出卷目录，不下载
+    pub json: bool, // 以JSON输出卷目录
+    pub gallery: bool, // 每卷生成插图集
+    pub gallery_only: bool, // 插图只放在插图集中，不保留在正文
+    pub compress: Option<PathBuf>, // 只把已有的EPUB工作目录压缩为EPUB，不爬取
+}
+
+/// 解析命令行参数，没有参数时返回None（进入交互模式）
+///
+/// 支持 `--category <sang-tac|ai-dich>`、可重复的 `--id <ID>` / `--novel-id <ID>`、`--dry-run`
+/// `--output-format <epub|txt|md>`（可重复或用逗号分隔，默认epub）、`--per-volume`、`--incremental`、`--respect-robots`、`--rtl`、`--merge-parts` 以及 `--base-url <URL>`。
+///
+/// `--gallery` 为每卷生成紧随卷封面的插图集，`--gallery-only` 则把插图从正文移到插图集中。
+///
+/// `--list-volumes` 只输出每卷的编号、卷id、章节数和标题（制表符分隔），加上 `--json` 时输出JSON。
+///
+/// `compress <工作目录>` 子命令把已有的EPUB工作目录（如 epub_{id}）重新压缩为EPUB，用于压缩失败后的恢复，工作目录会被保留。
+pub fn parse_cli_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<CliArgs>> {
+    let mut args = args.into_iter().peekable();
+    if args.peek().is_none() {
+        return Ok(None);
+    }
+
+    if args.peek().is_some_and(|arg| arg == "compress") {
+        args.next();
+        let work_dir = args.next().ok_or_else(|| DoclnError::InvalidInput("compress 需要一个工作目录".to_string()))?;
+        if let Some(arg) = args.next() {
+            return Err(DoclnError::InvalidInput(format!("未知参数: {}", arg)));
+        }
+        return Ok(Some(CliArgs {
+            category: NovelCategory::default(),
+            novel_ids: Vec::new(),
+            dry_run: false,
+            output_formats: Vec::new(),
+            per_volume: false,
+            base_url: None,
+            incremental: false,
+            respect_robots: false,
+            rtl: false,
+            merge_parts: false,
+            list_volumes: false,
+            json: false,
+            gallery: false,
+            gallery_only: false,
+            compress: Some(PathBuf::from(work_dir)),
+        }));
+    }
+
+    let mut category = NovelCategory::default();
+    let mut novel_ids = Vec::new();
+    let mut dry_run = false;
+    let mut output_formats = Vec::new();
+    let mut per_volume = false;
+    let mut base_url = None;
+    let mut incremental = false;
+    let mut respect_robots = false;
+    let mut rtl = false;
+    let mut merge_parts = false;
+    let mut list_volumes = false;
+    let mut json = false;
+    let mut gallery = false;
+    let mut gallery_only = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--category" | "-c" => {
+                let value = args.next().ok_or_else(|| DoclnError::InvalidInput("--category 需要一个值".to_string()))?;
+                category = NovelCategory::from_url_path(&value)
+                    .ok_or_else(|| DoclnError::InvalidInput(format!("未知的分类: {} (可选 sang-tac, ai-dich)", value)))?;
+            }
+            "--id" | "--novel-id" | "-i" => {
+                let value = args.next().ok_or_else(|| DoclnError::InvalidInput(format!("{} 需要一个值", arg)))?;
+                let novel_id = value.parse()
+                    .map_err(|_| DoclnError::InvalidInput(format!("请输入有效的小说ID (数字): {}", value)))?;
+                novel_ids.push(novel_id);
+            }
+            "--dry-run" => dry_run = true,
+            "--per-volume" => per_volume = true,
+            "--incremental" => incremental = true,
+            "--respect-robots" => respect_robots = true,
+            "--rtl" => rtl = true,
+            "--merge-parts" => merge_parts = true,
+            "--list-volumes" => list_volumes = true,
+            "--json" => json = true,
+            "--gallery" => gallery = true,
+            "--gallery-only" => gallery_only = true,
+            "--base-url" => {
+                base_url = Some(args.next().ok_or_else(|| DoclnError::InvalidInput("--base-url 需要一个值".to_string()))?);
+            }
+            "--output-format" | "-f" => {
+                let value = args.next().ok_or_else(|| DoclnError::InvalidInput(format!("{} 需要一个值", arg)))?;
+                for name in value.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                    let output_format = OutputFormat::from_name(name)
+                        .ok_or_else(|| DoclnError::InvalidInput(format!("未知的输出格式: {} (可选 epub, txt, md)", name)))?;
+                    if !output_formats.contains(&output_format) {
+                        output_formats.push(output_format);
+                    }
+                }
+            }
+            _ => return Err(DoclnError::InvalidInput(format!("未知参数: {}", arg))),
+        }
+    }
+
+    if novel_ids.is_empty() {
+        return Err(DoclnError::InvalidInput("至少需要一个 --id 参数".to_string()));
+    }
+
+    if output_formats.is_empty() {
+        output_formats.push(OutputFormat::Epub);
+    }
+
+    Ok(Some(CliArgs { category, novel_ids, dry_run, output_formats, per_volume, base_url, incremental, respect_robots, rtl, merge_parts, list_volumes, json, gallery, gallery_only, compress: None }))
+}
+
+/// 交互模式下读取用户输入的小说ID
+pub fn get_user_input() -> Result<u32> {
+    println!("请输入小说ID: ");
+    let mut novel_id = String::new();
+    io::stdin().read_line(&mut novel_id)?;
+    let novel_id: u32 = novel_id.trim().parse()
+        .map_err(|_| DoclnError::InvalidInput("请输入有效的小说ID (数字)".to_string()))?;
+    
+    Ok(novel_id)
+}
+
+/// 列出各小说的卷目录：默认每卷一行（编号、卷id、章节数、标题，制表符分隔），多本小说时以 `# {ID}` 行分隔；
+/// json为true时每本小说输出一行 `{"novel_id": ..., "volumes": [...]}`
+pub async fn run_list_volumes(crawler: &DoclnCrawler, novel_ids: &[u32], category: NovelCategory, json: bool) -> Result<()> {
+    for &novel_id in novel_ids {
+        let volumes = match crawler.list_volumes_in(novel_id, category).await {
+            Ok(volumes) => volumes,
+            Err(e) => {
+                error!("获取卷目录失败 (ID: {}): {}", novel_id, e);
+                continue;
+            }
+        };
+        if json {
+            println!("{}", serde_json::json!({ "novel_id": novel_id, "volumes": volumes }));
+            continue;
+        }
+        if novel_ids.len() > 1 {
+            println!("# {}", novel_id);
+        }
+        for volume in &volumes {
+            println!("{}", volume.to_tsv_line());
+        }
+    }
+    Ok(())
+}
+
+/// 交互模式：循环读取小说ID并爬取，直到用户选择退出
+pub async fn run_interactive(crawler: &DoclnCrawler) -> Result<()> {
+    loop {
+        println!("\n=== docln-fetch ===");
+        match get_user_input() {
+            Ok(novel_id) => {
+                println!("\n正在爬取 ID为 {} 的小说...", novel_id);
+                crawler.crawl_novel(novel_id).await;
+            }
+            Err(e) => {
+                println!("输入错误: {}", e);
+            }
+        }
+        
+        print!("\n是否继续爬取其他小说? (y/n): ");
+        io::stdout().flush()?;
+        let mut continue_choice = String::new();
+        io::stdin().read_line(&mut continue_choice)?;
+        if continue_choice.trim().to_lowercase() != "y" {
+            break;
+        }
+    }
+    
+    println!("程序结束。");
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+use reqwest::{StatusCode, Url};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use crate::error::DoclnError;
+
+/// Cloudflare验证/拦截页面中常见的标记
+const CLOUDFLARE_MARKERS: &[&str] = &[
+    "cf-browser-verification",
+    "cf-challenge",
+    "cf_chl_opt",
+    "<title>Just a moment...</title>",
+    "Attention Required! | Cloudflare",
+];
+
+/// 要求登录的页面中常见的提示文字和表单标记
+const LOGIN_MARKERS: &[&str] = &[
+    "Bạn cần đăng nhập",
+    "Vui lòng đăng nhập",
+    "id=\"login-form\"",
+];
+
+/// 站点“页面不存在”页面中的标记，用于识别以200返回的404页面
+const NOT_FOUND_MARKERS: &[&str] = &[
+    "<title>404",
+    "Không tìm thấy trang",
+    "Trang bạn tìm không tồn tại",
+];
+
+/// 读取Retry-After头（仅支持秒数形式）
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// 根据状态码和页面内容判断请求是否被限流或拦截
+///
+/// 这类响应往往带有正常的HTML内容，直接解析只会得到“未找到小说标题”之类的误导性错误，
+/// 因此在解析前单独识别出来。
+pub fn detect_block(status: StatusCode, retry_after: Option<Duration>, body: &str) -> Option<DoclnError> {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Some(DoclnError::RateLimited { retry_after });
+    }
+    // 验证页面以403/503返回，正常页面中也可能注入Cloudflare脚本，因此只检查这两种状态
+    let challenge_status = status == StatusCode::FORBIDDEN || status == StatusCode::SERVICE_UNAVAILABLE;
+    if challenge_status && CLOUDFLARE_MARKERS.iter().any(|marker| body.contains(marker)) {
+        return Some(DoclnError::BlockedByCloudflare);
+    }
+    None
+}
+
+/// 判断章节请求是否落到了登录页面（会员专属章节且未提供登录会话）
+///
+/// 站点对未登录的请求返回401、重定向到 /login，或在页面中显示登录提示；
+/// 页面仍包含章节正文时不视为需要登录。
+pub fn login_required(status: StatusCode, final_url: &Url, body: &str) -> bool {
+    if status == StatusCode::UNAUTHORIZED {
+        return true;
+    }
+    if final_url.path().trim_end_matches('/').ends_with("/login") {
+        return true;
+    }
+    !body.contains("id=\"chapter-content\"") && LOGIN_MARKERS.iter().any(|marker| body.contains(marker))
+}
+
+/// 判断小说页面请求是否得到了“页面不存在”的结果
+///
+/// 不存在的小说ID返回404，但响应体仍是完整的HTML页面，不提前识别的话只会在解析时得到
+/// “未找到小说标题”；部分情况下站点以200返回同样的页面，此时按页面标记判断。
+pub fn not_found(status: StatusCode, body: &str) -> bool {
+    if status == StatusCode::NOT_FOUND {
+        return true;
+    }
+    !body.contains("series-name") && NOT_FOUND_MARKERS.iter().any(|marker| body.contains(marker))
+}
@@ -0,0 +1,57 @@
+use crate::error::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use log::{debug, warn};
+
+/// 按章节URL记录正文内容哈希的旁路JSON文件
+///
+/// 站点不返回ETag/Last-Modified时，重新爬取仍会下载章节页面；若提取出的正文与上次哈希相同，
+/// 则不再重写XHTML（保留文件的修改时间）也不重新下载插图。克隆后共享同一份记录。
+#[derive(Clone)]
+pub struct ContentHashes {
+    path: PathBuf,
+    entries: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl ContentHashes {
+    /// 读取哈希文件，文件不存在或无法解析时从空记录开始
+    pub fn load<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("无法解析内容哈希文件 {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            path,
+            entries: Arc::new(Mutex::new(entries)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, url: &str) -> Option<String> {
+        self.entries.lock().unwrap().get(url).cloned()
+    }
+
+    pub fn insert(&self, url: &str, content_hash: String) {
+        self.entries.lock().unwrap().insert(url.to_string(), content_hash);
+    }
+
+    /// 写回哈希文件
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(&*self.entries.lock().unwrap())?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, content)?;
+        debug!("内容哈希已保存到: {}", self.path.display());
+        Ok(())
+    }
+}
@@ -1,34 +1,198 @@
-use anyhow::Result;
+use crate::error::{DoclnError, Result};
+use crate::utils::normalize_url;
+use crate::epub::{images_dir, StreamingEpubWriter};
+use crate::epub::stream::write_work_file;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
+use reqwest::header::CONTENT_TYPE;
+use super::{RequestLimiter, UserAgentRotator};
+use super::image_guard::{self, DEFAULT_MAX_IMAGE_PIXELS};
+use log::{debug, info, warn};
 
+/// 图片下载失败后的最大重试次数
+const MAX_IMAGE_RETRIES: usize = 2;
+
+#[derive(Clone)]
 pub struct ImageDownloader {
     client: reqwest::Client,
+    limiter: RequestLimiter,
+    user_agents: UserAgentRotator,
+    base_url: String,
+    max_image_pixels: u64,
+    overwrite: bool,
+    #[cfg(feature = "image-resize")]
+    image_resize: Option<super::ImageResizeOptions>,
+    stream: Option<StreamingEpubWriter>,
+    verbose: bool,
 }
 
 impl ImageDownloader {
-    pub fn new(client: reqwest::Client) -> Self {
-        Self { client }
+    pub fn new(client: reqwest::Client, limiter: RequestLimiter, user_agents: UserAgentRotator) -> Self {
+        Self {
+            client,
+            limiter,
+            user_agents,
+            base_url: super::DEFAULT_BASE_URL.to_string(),
+            max_image_pixels: DEFAULT_MAX_IMAGE_PIXELS,
+            overwrite: false,
+            #[cfg(feature = "image-resize")]
+            image_resize: Some(super::ImageResizeOptions::default()),
+            stream: None,
+            verbose: true,
+        }
+    }
+
+    /// 设置流式写入器：图片直接写入EPUB而不保存到工作目录
+    pub fn stream(mut self, stream: Option<StreamingEpubWriter>) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// 设置用于补全根相对图片地址的站点地址
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// 设置是否输出每个文件的下载详情
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// 设置允许的最大图片像素数
+    pub fn max_image_pixels(mut self, max_image_pixels: u64) -> Self {
+        self.max_image_pixels = max_image_pixels;
+        self
+    }
+
+    /// 设置是否重新下载已存在的图片（默认跳过已存在且非空的文件）
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// 设置下载后缩小过大的图片并重新编码，None表示保存原图
+    #[cfg(feature = "image-resize")]
+    pub fn image_resize(mut self, image_resize: Option<super::ImageResizeOptions>) -> Self {
+        self.image_resize = image_resize;
+        self
     }
 
     /// 通用的图片下载函数
+    ///
+    /// 文件扩展名根据图片实际内容（魔数）或Content-Type确定，保存为 {file_stem}.{扩展名}，
+    /// 返回实际使用的文件名。目标目录中已有同名且非空的图片时不再请求网络，直接返回已有文件名。
     pub async fn download_image(
         &self,
         image_url: &str,
-        filepath: &Path,
+        dir: &Path,
+        file_stem: &str,
         log_prefix: &str,
-    ) -> Result<()> {
-        println!("正在下载{}图片: {}", log_prefix, image_url);
+    ) -> Result<String> {
+        // 补全协议相对和根相对的图片地址
+        let image_url = normalize_url(image_url, &self.base_url);
+        let image_url = image_url.as_str();
         
-        // 下载图片
-        let response = self.client.get(image_url).send().await?;
-        let image_bytes = response.bytes().await?;
+        // 中断后重新运行时复用已下载的图片
+        if !self.overwrite
+            && let Some(filename) = existing_image(dir, file_stem, self.stream.as_ref())
+        {
+            if self.verbose {
+                debug!("{}图片已存在，跳过下载: {}", log_prefix, dir.join(&filename).display());
+            }
+            return Ok(filename);
+        }
+        
+        if self.verbose {
+            debug!("正在下载{}图片: {}", log_prefix, image_url);
+        }
+        
+        // 下载并校验图片
+        let (image_bytes, content_type) = self.fetch_image_bytes(image_url).await?;
+        image_guard::check_image_dimensions(&image_bytes, self.max_image_pixels)?;
+        #[cfg(feature = "image-resize")]
+        let image_bytes = self.resize_image(image_bytes, log_prefix);
         
         // 保存到本地
-        fs::write(filepath, &image_bytes)?;
+        let extension = detect_image_extension(&image_bytes, &content_type, image_url);
+        let filename = format!("{}.{}", file_stem, extension);
+        let filepath = dir.join(&filename);
+        write_work_file(self.stream.as_ref(), &filepath, &image_bytes)?;
         
-        println!("{}图片已保存到: {}", log_prefix, filepath.display());
-        Ok(())
+        if self.verbose {
+            debug!("{}图片已保存到: {}", log_prefix, filepath.display());
+        }
+        Ok(filename)
+    }
+
+    /// 按设置缩小并重新编码图片，处理失败时保留原图
+    #[cfg(feature = "image-resize")]
+    fn resize_image(&self, image_bytes: Vec<u8>, log_prefix: &str) -> Vec<u8> {
+        let Some(options) = &self.image_resize else {
+            return image_bytes;
+        };
+        match super::image_resize::optimize_image(&image_bytes, options) {
+            Ok(Some(optimized)) => {
+                if self.verbose {
+                    debug!("{}图片已压缩: {} -> {} 字节", log_prefix, image_bytes.len(), optimized.len());
+                }
+                optimized
+            }
+            Ok(None) => image_bytes,
+            Err(e) => {
+                warn!("{}图片压缩失败，保留原图: {}", log_prefix, e);
+                image_bytes
+            }
+        }
+    }
+
+    /// 下载图片内容，对网络错误和5xx进行重试
+    async fn fetch_image_bytes(&self, image_url: &str) -> Result<(Vec<u8>, String)> {
+        let mut attempt = 0;
+        loop {
+            match self.try_fetch_image_bytes(image_url).await {
+                Ok(image_bytes) => return Ok(image_bytes),
+                Err(e) => {
+                    // 4xx、非图片内容等错误重试也无济于事
+                    let retryable = match &e {
+                        DoclnError::Network(e) => e.status().is_none_or(|status| status.is_server_error()),
+                        _ => false,
+                    };
+                    if !retryable || attempt >= MAX_IMAGE_RETRIES {
+                        return Err(e);
+                    }
+                    attempt += 1;
+                    warn!("下载图片失败: {}，正在进行第 {} 次重试", e, attempt);
+                    tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+                }
+            }
+        }
+    }
+
+    /// 单次下载图片，检查状态码、Content-Type和内容长度，避免把错误页面保存为图片
+    async fn try_fetch_image_bytes(&self, image_url: &str) -> Result<(Vec<u8>, String)> {
+        // 许可持有到响应体读取完毕
+        let _permit = self.limiter.acquire_image().await;
+        let response = self.user_agents.apply(self.client.get(image_url)).send().await?;
+        let response = response.error_for_status()?;
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        if !content_type.is_empty() && !content_type.starts_with("image/") && !content_type.starts_with("application/octet-stream") {
+            return Err(DoclnError::Image(format!("响应不是图片 (Content-Type: {})", content_type)));
+        }
+
+        let image_bytes = response.bytes().await?;
+        if image_bytes.is_empty() {
+            return Err(DoclnError::Image("图片内容为空".to_string()));
+        }
+        Ok((image_bytes.to_vec(), content_type))
     }
 
     /// 通用的封面图片下载函数
@@ -36,22 +200,22 @@ impl ImageDownloader {
         &self,
         image_url: &str,
         images_dir: &Path,
-        filename: &str,
+        file_stem: &str,
         log_prefix: &str,
         skip_default: bool,
     ) -> Result<Option<String>> {
         // 检查是否为默认的nocover图片
         if skip_default && image_url.contains("nocover") {
-            println!("{}使用默认封面图片，跳过下载", log_prefix);
+            info!("{}使用默认封面图片，跳过下载", log_prefix);
             return Ok(None);
         }
         
-        let filepath = images_dir.join(filename);
-        
         // 使用通用函数下载图片
-        self.download_image(image_url, &filepath, log_prefix).await?;
+        let filename = self.download_image(image_url, images_dir, file_stem, log_prefix).await?;
         
-        println!("{}封面图片已保存到: {} (文件名: {})", log_prefix, filepath.display(), filename);
+        if self.verbose {
+            debug!("{}封面图片已保存到: {} (文件名: {})", log_prefix, images_dir.join(&filename).display(), filename);
+        }
         
         // 返回相对路径（相对于OEBPS目录）
         Ok(Some(format!("images/{}", filename)))
@@ -64,51 +228,89 @@ impl ImageDownloader {
         _title: &str,
         epub_dir: &Path,
     ) -> Result<Option<String>> {
-        // 从URL中提取文件扩展名
-        let extension = Path::new(image_url)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("jpg");
-        
         // EPUB标准目录结构: OEBPS/images/
-        let images_dir = epub_dir.join("OEBPS").join("images");
-        fs::create_dir_all(&images_dir)?;
-        
-        // 小说封面命名为cover
-        let filename = format!("cover.{}", extension);
+        let images_dir = images_dir(epub_dir);
+        if self.stream.is_none() {
+            fs::create_dir_all(&images_dir)?;
+        }
         
-        // 使用通用函数下载封面图片
-        self.download_cover_image_common(image_url, &images_dir, &filename, "小说", true).await
+        // 小说封面命名为cover，使用通用函数下载封面图片
+        self.download_cover_image_common(image_url, &images_dir, "cover", "小说", true).await
     }
 
     pub async fn download_volume_cover_image(
         &self,
         image_url: &str,
-        _volume_index: usize,
+        volume_index: usize,
         volume_title: &str,
         epub_dir: &Path,
     ) -> Result<Option<String>> {
-        // 从URL中提取文件扩展名
-        let extension = Path::new(image_url)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("jpg");
-        
-        // 清理卷标题中的特殊字符，用于文件名，并添加编号
+        // 清理卷标题中的特殊字符，用于文件名，并加上卷编号：不同卷可能同名（如各部都有"Tập 01"），
+        // 只用标题时后一卷会直接复用前一卷已下载的封面
         let safe_volume_title = volume_title
             .chars()
             .map(|c| if c.is_alphanumeric() || c == ' ' { c } else { '_' })
             .collect::<String>()
             .replace(' ', "_");
+        let file_stem = format!("volume_{:03}_{}", volume_index + 1, safe_volume_title);
         
         // EPUB标准目录结构: OEBPS/images/
-        let images_dir = epub_dir.join("OEBPS").join("images");
-        fs::create_dir_all(&images_dir)?;
-        
-        // 卷封面命名为卷名
-        let filename = format!("{}.{}", safe_volume_title, extension);
+        let images_dir = images_dir(epub_dir);
+        if self.stream.is_none() {
+            fs::create_dir_all(&images_dir)?;
+        }
         
-        // 使用通用函数下载卷封面图片
-        self.download_cover_image_common(image_url, &images_dir, &filename, &format!("卷 '{}' ", volume_title), true).await
+        // 卷封面命名为卷名，使用通用函数下载卷封面图片
+        self.download_cover_image_common(image_url, &images_dir, &file_stem, &format!("卷 '{}' ", volume_title), true).await
+    }
+}
+
+/// 查找目录中已下载的 {file_stem}.{扩展名} 图片，只认非空文件；流式写入时查找已写入EPUB的图片
+fn existing_image(dir: &Path, file_stem: &str, stream: Option<&StreamingEpubWriter>) -> Option<String> {
+    ["jpg", "png", "gif", "webp"]
+        .iter()
+        .map(|extension| format!("{}.{}", file_stem, extension))
+        .find(|filename| match stream {
+            Some(stream) => stream.contains_work_file(&dir.join(filename)),
+            None => fs::metadata(dir.join(filename)).map(|m| m.is_file() && m.len() > 0).unwrap_or(false),
+        })
+}
+
+/// 确定图片扩展名：优先依据文件头魔数，其次Content-Type，最后URL，默认jpg
+pub fn detect_image_extension(image_bytes: &[u8], content_type: &str, image_url: &str) -> &'static str {
+    if image_bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return "png";
+    }
+    if image_bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return "jpg";
+    }
+    if image_bytes.starts_with(b"GIF87a") || image_bytes.starts_with(b"GIF89a") {
+        return "gif";
+    }
+    if image_bytes.len() >= 12 && &image_bytes[0..4] == b"RIFF" && &image_bytes[8..12] == b"WEBP" {
+        return "webp";
+    }
+
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_lowercase();
+    match mime.as_str() {
+        "image/png" => return "png",
+        "image/jpeg" | "image/jpg" => return "jpg",
+        "image/gif" => return "gif",
+        "image/webp" => return "webp",
+        _ => {}
+    }
+
+    // 去掉查询参数后再从URL路径中取扩展名
+    let url_path = image_url.split(['?', '#']).next().unwrap_or(image_url);
+    match Path::new(url_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .as_deref()
+    {
+        Some("png") => "png",
+        Some("gif") => "gif",
+        Some("webp") => "webp",
+        _ => "jpg",
     }
 }
\ No newline at end of file
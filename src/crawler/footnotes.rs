@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use scraper::{ElementRef, Html, Selector};
+use crate::utils::escape_xml;
+
+/// 可能是注释条目的元素，id通常形如 note123、fn1、footnote-1
+const FOOTNOTE_SELECTORS: &[&str] = &["[id^='note']", "[id^='fn']", "[id^='footnote']"];
+
+/// 只用于在网页上显示提示框的注释图标，转为EPUB脚注后不再需要
+const NOTE_ICON_SELECTOR: &str = "span.note-icon";
+
+/// 章节脚注
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footnote {
+    pub number: usize, // 按正文中首次引用的顺序编号，从1开始
+    pub text: String,
+}
+
+/// 识别章节中的注释，将正文中的注释标记改写为EPUB脚注引用
+///
+/// 注释条目从整个页面中查找（注释区可能位于正文容器之外），正文中指向注释条目的
+/// 页内链接（如 `<sup><a href="#note1">[1]</a></sup>`）被替换为 `epub:type="noteref"` 链接，
+/// 已被引用的注释条目所在的段落从正文中移除，由 [`footnotes_xhtml`] 统一放到章节末尾。
+/// 无法对应到注释条目的标记和未被引用的注释保持原样。
+pub fn apply_footnotes(html: &str, paragraphs: Vec<String>) -> (Vec<String>, Vec<Footnote>) {
+    let document = Html::parse_document(html);
+    let notes = collect_notes(&document);
+    if notes.is_empty() {
+        return (paragraphs, Vec::new());
+    }
+
+    let link_selector = Selector::parse("a[href^='#']").unwrap();
+    let icon_selector = Selector::parse(NOTE_ICON_SELECTOR).unwrap();
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+    let mut reference_counts: HashMap<usize, usize> = HashMap::new();
+    let mut footnotes = Vec::new();
+
+    let mut linked_paragraphs = Vec::with_capacity(paragraphs.len());
+    for p_html in paragraphs {
+        let fragment = Html::parse_fragment(&p_html);
+        let mut modified_p_html = p_html.clone();
+
+        for link in fragment.select(&link_selector) {
+            let target = link.value().attr("href").unwrap_or("").trim_start_matches('#');
+            let Some(note) = notes.iter().find(|note| note.id == target) else {
+                continue;
+            };
+            let number = *numbers.entry(note.id.clone()).or_insert_with(|| {
+                footnotes.push(Footnote { number: footnotes.len() + 1, text: note.text.clone() });
+                footnotes.len()
+            });
+            // 同一条注释被多次引用时，第一次引用的id供脚注返回链接使用
+            let reference_count = reference_counts.entry(number).or_insert(0);
+            *reference_count += 1;
+            let noteref_id = if *reference_count == 1 {
+                format!("noteref-{}", number)
+            } else {
+                format!("noteref-{}-{}", number, reference_count)
+            };
+            let noteref = format!(
+                "<a epub:type=\"noteref\" href=\"#note-{}\" id=\"{}\">[{}]</a>",
+                number, noteref_id, number
+            );
+            modified_p_html = modified_p_html.replacen(&link.html(), &noteref, 1);
+        }
+        if modified_p_html != p_html {
+            for icon in fragment.select(&icon_selector) {
+                modified_p_html = modified_p_html.replacen(&icon.html(), "", 1);
+            }
+        }
+        linked_paragraphs.push(modified_p_html);
+    }
+
+    // 已转为脚注的注释条目不再作为正文段落重复出现
+    let linked_note_html: Vec<&String> = notes
+        .iter()
+        .filter(|note| numbers.contains_key(&note.id))
+        .flat_map(|note| &note.paragraph_html)
+        .collect();
+    let linked_paragraphs = linked_paragraphs
+        .into_iter()
+        .filter(|p_html| !linked_note_html.contains(&p_html))
+        .collect();
+
+    (linked_paragraphs, footnotes)
+}
+
+/// 生成章节末尾的脚注区，每条脚注带有返回正文的链接；没有脚注时返回空字符串
+pub fn footnotes_xhtml(footnotes: &[Footnote]) -> String {
+    if footnotes.is_empty() {
+        return String::new();
+    }
+    let mut xhtml = String::from("    <div class=\"footnotes\">\n    <hr/>\n");
+    for footnote in footnotes {
+        xhtml.push_str(&format!(
+            "    <aside epub:type=\"footnote\" id=\"note-{}\"><p><a href=\"#noteref-{}\">{}</a>. {}</p></aside>\n",
+            footnote.number, footnote.number, footnote.number, escape_xml(&footnote.text)
+        ));
+    }
+    xhtml.push_str("    </div>\n");
+    xhtml
+}
+
+/// 页面中的一条注释
+struct Note {
+    id: String,
+    text: String,
+    paragraph_html: Vec<String>, // 注释条目自身或其内部的段落HTML，用于从正文中剔除
+}
+
+/// 收集页面中的注释条目，只保留不再包含其他条目的最内层元素
+fn collect_notes(document: &Html) -> Vec<Note> {
+    let p_selector = Selector::parse("p").unwrap();
+    let candidates: Vec<ElementRef> = FOOTNOTE_SELECTORS
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .flat_map(|selector| document.select(&selector).collect::<Vec<_>>())
+        .collect();
+
+    let mut notes: Vec<Note> = Vec::new();
+    for candidate in &candidates {
+        let contains_other = candidate
+            .descendants()
+            .skip(1)
+            .filter_map(ElementRef::wrap)
+            .any(|descendant| candidates.iter().any(|other| other.id() == descendant.id()));
+        let id = candidate.value().attr("id").unwrap_or("").to_string();
+        // fnref1、noteref-1 之类是正文中的标记而不是注释条目
+        if contains_other || id.contains("ref") || notes.iter().any(|note| note.id == id) {
+            continue;
+        }
+
+        let text = candidate.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        let mut paragraph_html = vec![candidate.html()];
+        paragraph_html.extend(candidate.select(&p_selector).map(|p| p.html()));
+        notes.push(Note { id, text, paragraph_html });
+    }
+    notes
+}
@@ -0,0 +1,89 @@
+use crate::error::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Serialize, Deserialize};
+use log::{debug, warn};
+
+/// 单个章节URL的缓存校验信息
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    /// 从响应头中提取ETag和Last-Modified，两者都没有时返回None
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header_value = |name| headers.get(name).and_then(|value| value.to_str().ok()).map(|value| value.to_string());
+        let validators = Self {
+            etag: header_value(ETAG),
+            last_modified: header_value(LAST_MODIFIED),
+        };
+        (validators.etag.is_some() || validators.last_modified.is_some()).then_some(validators)
+    }
+
+    /// 为请求附加 If-None-Match / If-Modified-Since 条件头
+    pub fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let mut request = request;
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// 按章节URL记录ETag/Last-Modified的旁路JSON文件，用于重新爬取时发送条件请求
+///
+/// 克隆后共享同一份记录，可在并发的章节任务之间传递。
+#[derive(Clone)]
+pub struct HttpCache {
+    path: PathBuf,
+    entries: Arc<Mutex<HashMap<String, CacheValidators>>>,
+}
+
+impl HttpCache {
+    /// 读取缓存文件，文件不存在或无法解析时从空记录开始
+    pub fn load<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("无法解析HTTP缓存文件 {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self {
+            path,
+            entries: Arc::new(Mutex::new(entries)),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, url: &str) -> Option<CacheValidators> {
+        self.entries.lock().unwrap().get(url).cloned()
+    }
+
+    pub fn insert(&self, url: &str, validators: CacheValidators) {
+        self.entries.lock().unwrap().insert(url.to_string(), validators);
+    }
+
+    /// 写回缓存文件
+    pub fn save(&self) -> Result<()> {
+        let content = serde_json::to_string_pretty(&*self.entries.lock().unwrap())?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, content)?;
+        debug!("HTTP缓存已保存到: {}", self.path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use crate::error::{DoclnError, Result};
+
+/// 默认允许的最大图片像素数（1亿像素）
+pub const DEFAULT_MAX_IMAGE_PIXELS: u64 = 100_000_000;
+
+/// 仅解析图片头部声明的尺寸（不完整解码），拒绝超过像素上限的图片，防止解压炸弹
+///
+/// 无法从头部识别尺寸的数据同样拒绝：既无法确认其大小，也不能安全地交给缩放解码。
+pub fn check_image_dimensions(image_bytes: &[u8], max_pixels: u64) -> Result<()> {
+    match imagesize::blob_size(image_bytes) {
+        Ok(size) => {
+            let pixels = size.width as u64 * size.height as u64;
+            if pixels > max_pixels {
+                return Err(DoclnError::Image(format!(
+                    "图片尺寸 {}x{} 超过像素上限 {}，已跳过",
+                    size.width, size.height, max_pixels
+                )));
+            }
+            Ok(())
+        }
+        Err(e) => Err(DoclnError::Image(format!("无法识别图片尺寸，已跳过: {}", e))),
+    }
+}
@@ -0,0 +1,82 @@
+use crate::error::{DoclnError, Result};
+use std::io::Cursor;
+use image::{DynamicImage, ImageFormat};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+
+/// 默认的图片最大边长（像素），超过的图片按比例缩小
+pub const DEFAULT_MAX_IMAGE_DIMENSION: u32 = 2048;
+
+/// 默认的JPEG重新编码质量
+pub const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+/// 下载图片后的缩放和重新编码设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageResizeOptions {
+    pub max_dimension: u32, // 宽或高超过该值时按比例缩小
+    pub jpeg_quality: u8, // 1-100
+}
+
+impl Default for ImageResizeOptions {
+    fn default() -> Self {
+        Self {
+            max_dimension: DEFAULT_MAX_IMAGE_DIMENSION,
+            jpeg_quality: DEFAULT_JPEG_QUALITY,
+        }
+    }
+}
+
+/// 缩小过大的图片并重新编码，图片无需处理时返回None
+///
+/// 超过最大边长的图片按比例缩小：带透明通道的非JPEG图片保存为PNG，其余保存为JPEG；
+/// 尺寸合适的JPEG按设定质量重新编码，只有变小时才采用。GIF可能是动图，保持原样。
+/// 返回的数据格式可能与原图不同，调用方应按内容重新确定扩展名。
+pub fn optimize_image(image_bytes: &[u8], options: &ImageResizeOptions) -> Result<Option<Vec<u8>>> {
+    let format = image::guess_format(image_bytes).map_err(image_error)?;
+    if format == ImageFormat::Gif {
+        return Ok(None);
+    }
+
+    let image = image::load_from_memory_with_format(image_bytes, format).map_err(image_error)?;
+    let max_dimension = options.max_dimension.max(1);
+    let needs_resize = image.width() > max_dimension || image.height() > max_dimension;
+    if !needs_resize && format != ImageFormat::Jpeg {
+        return Ok(None);
+    }
+
+    let image = if needs_resize {
+        image.resize(max_dimension, max_dimension, FilterType::Lanczos3)
+    } else {
+        image
+    };
+    let encoded = if format != ImageFormat::Jpeg && image.color().has_alpha() {
+        encode_png(&image)?
+    } else {
+        encode_jpeg(&image, options.jpeg_quality)?
+    };
+
+    if !needs_resize && encoded.len() >= image_bytes.len() {
+        return Ok(None);
+    }
+    Ok(Some(encoded))
+}
+
+fn encode_jpeg(image: &DynamicImage, quality: u8) -> Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    // JPEG不支持透明通道
+    let rgb_image = DynamicImage::ImageRgb8(image.to_rgb8());
+    JpegEncoder::new_with_quality(&mut encoded, quality.clamp(1, 100))
+        .encode_image(&rgb_image)
+        .map_err(image_error)?;
+    Ok(encoded)
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>> {
+    let mut encoded = Vec::new();
+    image.write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png).map_err(image_error)?;
+    Ok(encoded)
+}
+
+fn image_error(e: image::ImageError) -> DoclnError {
+    DoclnError::Image(format!("图片处理失败: {}", e))
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 默认的全局最大并发请求数
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 8;
+/// 默认的章节并发请求数
+pub const DEFAULT_MAX_CHAPTERS: usize = 4;
+/// 默认的图片并发请求数
+pub const DEFAULT_MAX_IMAGES: usize = 4;
+
+/// HTTP请求并发限制器
+///
+/// 章节和图片各有一个软限制，另有一个全局上限约束所有正在进行的请求，
+/// 保证 章节 × 图片 的嵌套并发不会超过全局上限。
+#[derive(Clone)]
+pub struct RequestLimiter {
+    global: Arc<Semaphore>,
+    chapters: Arc<Semaphore>,
+    images: Arc<Semaphore>,
+}
+
+/// 请求许可，在请求结束前持有，drop时释放
+pub struct RequestPermit {
+    _kind: OwnedSemaphorePermit,
+    _global: OwnedSemaphorePermit,
+}
+
+impl Default for RequestLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IN_FLIGHT, DEFAULT_MAX_CHAPTERS, DEFAULT_MAX_IMAGES)
+    }
+}
+
+impl RequestLimiter {
+    pub fn new(max_in_flight: usize, max_chapters: usize, max_images: usize) -> Self {
+        // 限制值至少为1，否则请求将永远无法获得许可
+        Self {
+            global: Arc::new(Semaphore::new(max_in_flight.max(1))),
+            chapters: Arc::new(Semaphore::new(max_chapters.max(1))),
+            images: Arc::new(Semaphore::new(max_images.max(1))),
+        }
+    }
+
+    /// 获取章节（页面）请求许可
+    pub async fn acquire_chapter(&self) -> RequestPermit {
+        Self::acquire(&self.chapters, &self.global).await
+    }
+
+    /// 获取图片请求许可
+    pub async fn acquire_image(&self) -> RequestPermit {
+        Self::acquire(&self.images, &self.global).await
+    }
+
+    /// 当前可用的全局许可数
+    pub fn available(&self) -> usize {
+        self.global.available_permits()
+    }
+
+    async fn acquire(kind: &Arc<Semaphore>, global: &Arc<Semaphore>) -> RequestPermit {
+        // 先获取分类许可再获取全局许可，避免等待分类许可时占用全局名额
+        let kind_permit = kind.clone().acquire_owned().await.expect("semaphore closed");
+        let global_permit = global.clone().acquire_owned().await.expect("semaphore closed");
+        RequestPermit {
+            _kind: kind_permit,
+            _global: global_permit,
+        }
+    }
+}
+
+/// 章节请求之间的随机间隔范围
+///
+/// 每次在[min, max]内均匀取值，避免固定间隔形成明显的自动化请求模式；min与max相等时为固定间隔。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestDelay {
+    min: Duration,
+    max: Duration,
+}
+
+impl RequestDelay {
+    /// 创建间隔范围，min大于max时两者互换
+    pub fn new(min: Duration, max: Duration) -> Self {
+        if min > max {
+            Self { min: max, max: min }
+        } else {
+            Self { min, max }
+        }
+    }
+
+    /// 固定间隔
+    pub fn fixed(delay: Duration) -> Self {
+        Self { min: delay, max: delay }
+    }
+
+    pub fn min(&self) -> Duration {
+        self.min
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// 以floor为下限调整范围，如使用robots.txt的Crawl-delay作为最小间隔
+    pub fn at_least(self, floor: Duration) -> Self {
+        Self {
+            min: self.min.max(floor),
+            max: self.max.max(floor),
+        }
+    }
+
+    /// 在范围内随机取一个间隔
+    pub fn sample(&self) -> Duration {
+        if self.min == self.max {
+            return self.min;
+        }
+        let min_nanos = self.min.as_nanos().min(u64::MAX as u128) as u64;
+        let max_nanos = self.max.as_nanos().min(u64::MAX as u128) as u64;
+        Duration::from_nanos(fastrand::u64(min_nanos..=max_nanos))
+    }
+}
+
+impl From<Duration> for RequestDelay {
+    fn from(delay: Duration) -> Self {
+        Self::fixed(delay)
+    }
+}
@@ -0,0 +1,125 @@
+use crate::error::Result;
+use std::fs;
+use std::path::Path;
+use scraper::{ElementRef, Html, Selector};
+use super::{Chapter, ChapterProcessor, Epub};
+use log::{debug, info};
+
+/// 表示分段的标题后缀，不区分大小写
+const PART_WORDS: &[&str] = &["phần", "part"];
+
+/// 拆分 "Chương 5 - Phần 1"、"Chương 5 (Part 2)" 这类分段标题，返回 (基础标题, 段号)
+///
+/// 只识别 "Phần N" 或 "Part N" 结尾的标题，基础标题可以为空（后续分段的标题常常只有 "Phần 2"）。
+pub fn split_part_title(title: &str) -> Option<(String, u32)> {
+    let title = title.trim().trim_end_matches(')').trim_end();
+    let digit_count = title.chars().rev().take_while(char::is_ascii_digit).count();
+    if digit_count == 0 {
+        return None;
+    }
+    let number = title[title.len() - digit_count..].parse().ok()?;
+    let rest = title[..title.len() - digit_count].trim_end();
+
+    for word in PART_WORDS {
+        let Some((split, _)) = rest.char_indices().rev().nth(word.chars().count() - 1) else {
+            continue;
+        };
+        if rest[split..].to_lowercase() != *word {
+            continue;
+        }
+        // 后缀前必须是分隔符或标题开头，避免匹配到单词的一部分
+        if rest[..split].chars().next_back().is_some_and(char::is_alphanumeric) {
+            continue;
+        }
+        let base = rest[..split].trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '–' | '—' | ':' | '(' | ','));
+        return Some((base.to_string(), number));
+    }
+    None
+}
+
+/// 将同一卷中连续的分段章节合并为一章，返回合并的组数
+///
+/// 只合并从第1段开始、段号连续、基础标题相同（后续分段可以只有段号）且都已下载的章节；
+/// 含脚注的章节不合并，避免脚注编号冲突。合并后的XHTML写入第一段旁边的 chapter_XXX_merged.xhtml，
+/// 各分段的原文件保持不变，续传和增量更新仍按原来的章节处理。插图沿用各分段原有的文件和编号。
+pub fn merge_split_chapters(epub: &mut Epub, oebps_dir: &Path) -> Result<usize> {
+    let mut merged_groups = 0;
+    for volume in &mut epub.volumes {
+        let mut i = 0;
+        while i < volume.chapters.len() {
+            let end = part_group_end(&volume.chapters, i);
+            if end - i < 2 {
+                i += 1;
+                continue;
+            }
+            match merge_chapters(&volume.chapters[i..end], oebps_dir, &epub.language)? {
+                Some(merged) => {
+                    info!("已合并分段章节: {} ({} 段)", merged.title, end - i);
+                    volume.chapters.splice(i..end, [merged]);
+                    merged_groups += 1;
+                    i += 1;
+                }
+                None => i = end,
+            }
+        }
+    }
+    Ok(merged_groups)
+}
+
+/// 从start开始的分段章节组的结束位置（不含），start不是第1段时返回start
+fn part_group_end(chapters: &[Chapter], start: usize) -> usize {
+    let Some((base, 1)) = split_part_title(&chapters[start].title) else {
+        return start;
+    };
+    if base.is_empty() || chapters[start].xhtml_path.is_none() {
+        return start;
+    }
+    let mut end = start + 1;
+    while let Some(chapter) = chapters.get(end)
+        && chapter.xhtml_path.is_some()
+        && let Some((next_base, number)) = split_part_title(&chapter.title)
+        && number as usize == end - start + 1
+        && (next_base.is_empty() || next_base == base)
+    {
+        end += 1;
+    }
+    end
+}
+
+/// 合并一组分段章节，写入合并后的XHTML；有分段含脚注时返回None
+fn merge_chapters(parts: &[Chapter], oebps_dir: &Path, language: &str) -> Result<Option<Chapter>> {
+    let content_selector = Selector::parse("div.chapter-content").unwrap();
+    let footnotes_selector = Selector::parse("div.footnotes").unwrap();
+
+    let mut paragraphs = Vec::new();
+    for part in parts {
+        let xhtml_path = part.xhtml_path.as_deref().unwrap_or_default();
+        let document = Html::parse_document(&fs::read_to_string(oebps_dir.join(xhtml_path))?);
+        if document.select(&footnotes_selector).next().is_some() {
+            debug!("章节 '{}' 含脚注，不合并", part.title);
+            return Ok(None);
+        }
+        if let Some(content) = document.select(&content_selector).next() {
+            paragraphs.extend(content.children().filter_map(ElementRef::wrap).map(|element| element.html()));
+        }
+    }
+
+    let first = &parts[0];
+    let (title, _) = split_part_title(&first.title).unwrap_or_default();
+    let xhtml_path = first.xhtml_path.as_deref().unwrap_or_default();
+    let merged_path = match xhtml_path.strip_suffix(".xhtml") {
+        Some(stem) => format!("{}_merged.xhtml", stem),
+        None => format!("{}_merged", xhtml_path),
+    };
+    let xhtml_content = ChapterProcessor::build_chapter_xhtml(&title, first.published.as_deref(), &paragraphs, &[], language);
+    fs::write(oebps_dir.join(&merged_path), xhtml_content)?;
+
+    let mut merged = first.clone();
+    merged.title = title;
+    merged.xhtml_path = Some(merged_path);
+    merged.has_illustrations = parts.iter().any(|part| part.has_illustrations);
+    merged.illustration_paths = parts.iter().flat_map(|part| part.illustration_paths.iter().cloned()).collect();
+    merged.char_count = parts.iter().map(|part| part.char_count).sum();
+    merged.word_count = parts.iter().map(|part| part.word_count).sum();
+    Ok(Some(merged))
+}
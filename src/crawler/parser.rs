@@ -1,111 +1,243 @@
-use anyhow::Result;
-use scraper::{Html, Selector, Element};
-use crate::epub::Epub;
-use crate::epub::chapter::Chapter;
+use crate::error::{DoclnError, Result};
+use std::collections::{HashMap, HashSet};
+use scraper::{ElementRef, Html, Selector};
+use super::{Chapter, Epub, NovelStatus};
+use crate::epub::DEFAULT_LANGUAGE;
+use crate::utils::{normalize_whitespace, parse_date};
+use chrono::NaiveDate;
+use log::info;
 
+#[derive(Debug, Clone, Copy, Default)]
 pub struct NovelParser;
 
 impl NovelParser {
     pub fn parse_novel_info(&self, html_content: &str, url: &str, novel_id: u32) -> Result<Epub> {
         let document = Html::parse_document(html_content);
         
-        // 解析小说标题
+        let title = self.parse_title(&document).ok_or_else(|| DoclnError::Parse("未找到小说标题".to_string()))?;
+        let author = self.parse_author(&document).ok_or_else(|| DoclnError::Parse("未找到作者信息".to_string()))?;
+        let (genres, tags) = self.parse_genres_and_tags(&document);
+
+        // 创建Epub结构体（其他字段将在后续处理中填充）
+        let epub = Epub {
+            id: novel_id,
+            other_titles: self.parse_other_titles(&document, &title),
+            title,
+            author,
+            illustrator: self.parse_illustrator(&document),
+            status: self.parse_status(&document),
+            rating: self.parse_statistic(&document, "Đánh giá").and_then(|value| parse_rating(&value)),
+            views: self.parse_statistic(&document, "Lượt xem").and_then(|value| parse_count(&value)),
+            likes: self.parse_statistic(&document, "Lượt thích").and_then(|value| parse_count(&value)),
+            language: DEFAULT_LANGUAGE.to_string(),
+            summary: self.parse_summary(&document),
+            cover_image_path: None,
+            volumes: Vec::new(),
+            genres,
+            tags,
+            updated: self.parse_last_updated(&document).map(|date| date.format("%Y-%m-%d").to_string()),
+            url: url.to_string(),
+        };
+
+        Ok(epub)
+    }
+
+    /// 解析小说标题
+    pub fn parse_title(&self, document: &Html) -> Option<String> {
         let title_selector = Selector::parse("span.series-name > a").unwrap();
-        let title = document
+        document
             .select(&title_selector)
             .next()
-            .ok_or_else(|| anyhow::anyhow!("未找到小说标题"))?
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string();
+            .map(|element| normalize_whitespace(&element.text().collect::<String>()))
+            .filter(|title| !title.is_empty())
+    }
 
-        // 解析作者和插画师信息
-        let mut author = String::new();
-        let mut illustrator = None;
-        let info_item_selector = Selector::parse("div.info-item").unwrap();
-        let info_name_selector = Selector::parse("span.info-name").unwrap();
-        let info_value_selector = Selector::parse("span.info-value > a").unwrap();
+    /// 解析其他名称（原名、罗马音等），没有该信息块时返回空列表
+    pub fn parse_other_titles(&self, document: &Html, title: &str) -> Vec<String> {
+        let fact_item_selector = Selector::parse("div.fact-item, div.info-item").unwrap();
+        let fact_name_selector = Selector::parse("div.fact-name, span.info-name").unwrap();
+        let fact_value_selector = Selector::parse("div.fact-value, span.info-value").unwrap();
         
-        for info_item in document.select(&info_item_selector) {
-            if let Some(info_name) = info_item.select(&info_name_selector).next() {
-                let info_name_text = info_name.text().collect::<String>();
-                
-                if info_name_text.contains("Tác giả:") {
-                    // 解析作者
-                    if let Some(author_link) = info_item.select(&info_value_selector).next() {
-                        author = author_link.text().collect::<String>().trim().to_string();
-                    }
-                } else if info_name_text.contains("Họa sĩ:") {
-                    // 解析插画师
-                    if let Some(illustrator_link) = info_item.select(&info_value_selector).next() {
-                        let illustrator_text = illustrator_link.text().collect::<String>().trim().to_string();
-                        if !illustrator_text.is_empty() {
-                            illustrator = Some(illustrator_text);
-                        }
-                    }
+        let mut other_titles: Vec<String> = Vec::new();
+        for fact_item in document.select(&fact_item_selector) {
+            let Some(fact_name) = fact_item.select(&fact_name_selector).next() else {
+                continue;
+            };
+            if !fact_name.text().collect::<String>().contains("Tên khác") {
+                continue;
+            }
+            let Some(fact_value) = fact_item.select(&fact_value_selector).next() else {
+                continue;
+            };
+            
+            // 每个名称通常单独一个子元素，否则按换行拆分纯文本
+            let mut entries: Vec<String> = fact_value
+                .children()
+                .filter_map(ElementRef::wrap)
+                .map(|entry| normalize_whitespace(&entry.text().collect::<String>()))
+                .collect();
+            if entries.is_empty() {
+                entries = fact_value.text().collect::<String>().lines().map(normalize_whitespace).collect();
+            }
+            for entry in entries {
+                if !entry.is_empty() && entry != title && !other_titles.contains(&entry) {
+                    other_titles.push(entry);
                 }
             }
         }
+        other_titles
+    }
 
-        if author.is_empty() {
-            return Err(anyhow::anyhow!("未找到作者信息"));
-        }
+    /// 解析作者
+    pub fn parse_author(&self, document: &Html) -> Option<String> {
+        self.parse_info_value(document, "Tác giả:")
+    }
 
-        // 解析简介内容
-        let mut summary = String::new();
-        let summary_selector = Selector::parse("div.summary-content > p").unwrap();
-        let summary_paragraphs: Vec<String> = document
-            .select(&summary_selector)
-            .map(|p| p.text().collect::<String>().trim().to_string())
-            .filter(|text| !text.is_empty())
-            .collect();
+    /// 解析插画师
+    pub fn parse_illustrator(&self, document: &Html) -> Option<String> {
+        self.parse_info_value(document, "Họa sĩ:")
+    }
+
+    /// 解析连载状态
+    pub fn parse_status(&self, document: &Html) -> Option<NovelStatus> {
+        self.parse_info_value(document, "Tình trạng:").map(|label| NovelStatus::from_label(&label))
+    }
+
+    /// 解析统计栏（评分、浏览量等）中指定名称对应的原始文本，页面没有该项时返回None
+    pub fn parse_statistic(&self, document: &Html, statistic_name: &str) -> Option<String> {
+        let statistic_item_selector = Selector::parse("div.statistic-item").unwrap();
+        let statistic_name_selector = Selector::parse("div.statistic-name").unwrap();
+        let statistic_value_selector = Selector::parse("div.statistic-value").unwrap();
         
-        if !summary_paragraphs.is_empty() {
-            summary = summary_paragraphs.join("\n");
+        document
+            .select(&statistic_item_selector)
+            .find(|item| {
+                item.select(&statistic_name_selector)
+                    .next()
+                    .is_some_and(|name| name.text().collect::<String>().contains(statistic_name))
+            })
+            .and_then(|item| item.select(&statistic_value_selector).next())
+            .map(|value| normalize_whitespace(&value.text().collect::<String>()))
+            .filter(|value| !value.is_empty())
+    }
+
+    /// 解析卷header所在区域中单独标注的插画师，卷没有标注时返回None
+    pub fn parse_volume_illustrator(&self, document: &Html, volume_id: &str) -> Option<String> {
+        let info_item_selector = Selector::parse("div.info-item").unwrap();
+        self.info_value_in(self.volume_elements(document, volume_id, &info_item_selector), "Họa sĩ:")
+    }
+
+    /// 解析统计栏中的最后更新日期（"Lần cuối"），优先使用time元素的datetime属性，其次是title属性和文字
+    pub fn parse_last_updated(&self, document: &Html) -> Option<NaiveDate> {
+        let statistic_item_selector = Selector::parse("div.statistic-item").unwrap();
+        let statistic_name_selector = Selector::parse("div.statistic-name").unwrap();
+        let time_selector = Selector::parse("div.statistic-value time").unwrap();
+
+        let item = document.select(&statistic_item_selector).find(|item| {
+            item.select(&statistic_name_selector)
+                .next()
+                .is_some_and(|name| name.text().collect::<String>().contains("Lần cuối"))
+        })?;
+        if let Some(time) = item.select(&time_selector).next() {
+            let time = time.value();
+            if let Some(date) = time.attr("datetime").or(time.attr("title")).and_then(parse_date) {
+                return Some(date);
+            }
         }
+        self.parse_statistic(document, "Lần cuối").and_then(|value| parse_date(&value))
+    }
+
+    /// 解析信息栏中指定名称对应的值
+    fn parse_info_value(&self, document: &Html, info_name: &str) -> Option<String> {
+        let info_item_selector = Selector::parse("div.info-item").unwrap();
+        self.info_value_in(document.select(&info_item_selector), info_name)
+    }
 
-        // 解析标签
-        let mut tags = Vec::new();
-        let tags_selector = Selector::parse("div.series-gernes > a").unwrap();
-        for tag_element in document.select(&tags_selector) {
-            let tag_text = tag_element.text().collect::<String>().trim().to_string();
-            if !tag_text.is_empty() {
-                tags.push(tag_text);
+    /// 在给定的信息栏条目中查找指定名称对应的值，优先取值中的链接文字，没有链接时（如作者未建立页面）取纯文本
+    fn info_value_in<'a>(&self, info_items: impl IntoIterator<Item = ElementRef<'a>>, info_name: &str) -> Option<String> {
+        let info_name_selector = Selector::parse("span.info-name").unwrap();
+        let info_value_selector = Selector::parse("span.info-value").unwrap();
+        let link_selector = Selector::parse("a").unwrap();
+        
+        for info_item in info_items {
+            if let Some(info_name_element) = info_item.select(&info_name_selector).next()
+                && info_name_element.text().collect::<String>().contains(info_name)
+            {
+                let info_value = info_item.select(&info_value_selector).next()?;
+                let element = info_value.select(&link_selector).next().unwrap_or(info_value);
+                return Some(normalize_whitespace(&element.text().collect::<String>())).filter(|value| !value.is_empty());
             }
         }
+        None
+    }
 
-        // 创建Epub结构体（其他字段将在后续处理中填充）
-        let epub = Epub {
-            id: novel_id,
-            title,
-            author,
-            illustrator,
-            summary,
-            cover_image_path: None,
-            volumes: Vec::new(),
-            tags,
-            url: url.to_string(),
-        };
+    /// 解析简介内容，每个段落一项
+    pub fn parse_summary(&self, document: &Html) -> Vec<String> {
+        let summary_selector = Selector::parse("div.summary-content > p").unwrap();
+        document
+            .select(&summary_selector)
+            .map(|p| normalize_whitespace(&p.text().collect::<String>()))
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
 
-        Ok(epub)
+    /// 解析标签
+    pub fn parse_tags(&self, document: &Html) -> Vec<String> {
+        self.parse_link_texts(document, "div.series-gernes > a")
+    }
+
+    /// 解析分类和用户标签，返回 (genres, tags)
+    ///
+    /// 页面另有用户标签列表时，series-gernes 中的是官方分类；
+    /// 只有一个列表时分类为空，该列表仍作为标签，与之前的行为一致。
+    pub fn parse_genres_and_tags(&self, document: &Html) -> (Vec<String>, Vec<String>) {
+        let user_tags = self.parse_link_texts(document, "div.series-tags > a");
+        if user_tags.is_empty() {
+            (Vec::new(), self.parse_tags(document))
+        } else {
+            (self.parse_tags(document), user_tags)
+        }
+    }
+
+    fn parse_link_texts(&self, document: &Html, selector: &str) -> Vec<String> {
+        let link_selector = Selector::parse(selector).unwrap();
+        document
+            .select(&link_selector)
+            .map(|link_element| normalize_whitespace(&link_element.text().collect::<String>()))
+            .filter(|link_text| !link_text.is_empty())
+            .collect()
     }
 
+    /// 提取小说封面地址
+    ///
+    /// 优先从封面容器的 `background-image: url(...)` 样式中提取，无法解析时依次退回到
+    /// 封面容器内的 `<img>` 和页面的 `og:image`。
     pub fn extract_cover_url(&self, document: &Html) -> Option<String> {
         let cover_selector = Selector::parse("div.content.img-in-ratio").unwrap();
-        if let Some(cover_div) = document.select(&cover_selector).next() {
-            if let Some(style) = cover_div.value().attr("style") {
-                // 从style属性中提取URL: background-image: url('...')
-                if let Some(start) = style.find("url('") {
-                    let start = start + 5; // 跳过 "url('"
-                    if let Some(end) = style[start..].find("')") {
-                        let image_url = &style[start..start + end];
-                        return Some(image_url.to_string());
-                    }
-                }
-            }
-        }
-        None
+        let cover_img_selector = Selector::parse("div.series-cover img, div.content.img-in-ratio img").unwrap();
+        let og_image_selector = Selector::parse("meta[property='og:image']").unwrap();
+
+        document
+            .select(&cover_selector)
+            .next()
+            .and_then(|cover_div| cover_div.value().attr("style"))
+            .and_then(css_url)
+            .or_else(|| {
+                document
+                    .select(&cover_img_selector)
+                    .find_map(|img| img.value().attr("data-src").or_else(|| img.value().attr("src")))
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(str::to_string)
+            })
+            .or_else(|| {
+                document
+                    .select(&og_image_selector)
+                    .find_map(|meta| meta.value().attr("content"))
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(str::to_string)
+            })
     }
 
     pub fn parse_volume_info(&self, document: &Html) -> Vec<(String, String)> {
@@ -115,26 +247,26 @@ impl NovelParser {
         let volume_item_selector = Selector::parse("li").unwrap();
         let volume_title_selector = Selector::parse("span.list_vol-title").unwrap();
         
-        if let Some(list_vol_section) = document.select(&list_vol_section_selector).next() {
-            if let Some(list_volume) = list_vol_section.select(&list_volume_selector).next() {
-                for volume_item in list_volume.select(&volume_item_selector) {
-                    // 获取卷标题
-                    let volume_title = volume_item
-                        .select(&volume_title_selector)
-                        .next()
-                        .map(|span| span.text().collect::<String>().trim().to_string())
-                        .unwrap_or_else(|| "未知卷".to_string());
-                    
-                    // 获取卷的data-scrollto属性
-                    let volume_id = volume_item
-                        .value()
-                        .attr("data-scrollto")
-                        .unwrap_or("")
-                        .to_string();
-                    
-                    if !volume_id.is_empty() {
-                        volumes.push((volume_title, volume_id));
-                    }
+        if let Some(list_vol_section) = document.select(&list_vol_section_selector).next()
+            && let Some(list_volume) = list_vol_section.select(&list_volume_selector).next()
+        {
+            for volume_item in list_volume.select(&volume_item_selector) {
+                // 获取卷标题
+                let volume_title = volume_item
+                    .select(&volume_title_selector)
+                    .next()
+                    .map(|span| normalize_whitespace(&span.text().collect::<String>()))
+                    .unwrap_or_else(|| "未知卷".to_string());
+                
+                // 获取卷的data-scrollto属性
+                let volume_id = volume_item
+                    .value()
+                    .attr("data-scrollto")
+                    .unwrap_or("")
+                    .to_string();
+                
+                if !volume_id.is_empty() {
+                    volumes.push((volume_title, volume_id));
                 }
             }
         }
@@ -143,79 +275,228 @@ impl NovelParser {
     }
 
     pub fn parse_volume_chapters(&self, document: &Html, volume_id: &str) -> Vec<Chapter> {
-        let mut chapters = Vec::new();
+        let mut chapters: Vec<Chapter> = Vec::new();
+        let chapter_item_selector = Selector::parse("li").unwrap();
         
-        // 根据volume_id找到对应的卷元素
+        if let Some(chapters_list) = self.find_volume_chapter_list(document, volume_id) {
+            for chapter_item in chapters_list.select(&chapter_item_selector) {
+                let Some(chapter) = self.parse_chapter_item(chapter_item) else {
+                    continue;
+                };
+                // 置顶等原因重复列出的章节只保留第一次出现的位置
+                if chapters.iter().any(|c| c.url == chapter.url) {
+                    info!("跳过重复的章节: {} ({})", chapter.title, chapter.url);
+                    continue;
+                }
+                chapters.push(chapter);
+            }
+        }
+        
+        chapters
+    }
+
+    /// 根据卷的data-scrollto id找到该卷的章节列表
+    fn find_volume_chapter_list<'a>(&self, document: &'a Html, volume_id: &str) -> Option<ElementRef<'a>> {
+        let list_chapters_selector = Selector::parse("ul.list-chapters").unwrap();
+        self.volume_elements(document, volume_id, &list_chapters_selector).into_iter().next()
+    }
+
+    /// 按文档顺序返回属于某一卷的、匹配selector的元素
+    ///
+    /// 卷的区域是从卷header向上最近的包含章节列表的祖先（不越过包含其他卷header的容器），
+    /// 兼容header与内容不是兄弟节点的嵌套布局；找不到时（各卷的header和内容平铺在同一容器中）
+    /// 取header之后、下一个卷header之前的文档区域。
+    fn volume_elements<'a>(&self, document: &'a Html, volume_id: &str, selector: &Selector) -> Vec<ElementRef<'a>> {
         let volume_element_id = volume_id.trim_start_matches('#');
-        let volume_header_selector = Selector::parse(&format!("header#{}", volume_element_id)).unwrap();
+        let Ok(volume_header_selector) = Selector::parse(&format!("header[id=\"{}\"]", volume_element_id)) else {
+            return Vec::new();
+        };
+        let any_volume_header_selector = Selector::parse("header[id]").unwrap();
         let list_chapters_selector = Selector::parse("ul.list-chapters").unwrap();
-        let chapter_item_selector = Selector::parse("li").unwrap();
-        let chapter_name_selector = Selector::parse("div.chapter-name").unwrap();
-        let chapter_link_selector = Selector::parse("a").unwrap();
-        let illustration_icon_selector = Selector::parse("i").unwrap();
         
-        if let Some(volume_header) = document.select(&volume_header_selector).next() {
-            if let Some(parent_element) = volume_header.parent_element() {
-                // 在该卷元素中查找章节列表
-                if let Some(chapters_list) = parent_element.select(&list_chapters_selector).next() {
-                    for chapter_item in chapters_list.select(&chapter_item_selector) {
-                        // 查找章节名称和链接
-                        if let Some(chapter_name_div) = chapter_item.select(&chapter_name_selector).next() {
-                            if let Some(chapter_link) = chapter_name_div.select(&chapter_link_selector).next() {
-                                let chapter_title = chapter_link
-                                    .text()
-                                    .collect::<String>()
-                                    .trim()
-                                    .to_string();
-                                
-                                let chapter_url = chapter_link
-                                    .value()
-                                    .attr("href")
-                                    .unwrap_or("")
-                                    .to_string();
-                                
-                                // 检查是否包含插图图标
-                                let has_illustrations = chapter_name_div.select(&illustration_icon_selector).next().is_some();
-                                
-                                if !chapter_title.is_empty() && !chapter_url.is_empty() {
-                                    chapters.push(Chapter {
-                                        title: chapter_title,
-                                        url: chapter_url,
-                                        has_illustrations,
-                                        xhtml_path: None,
-                                    });
-                                }
-                            }
-                        }
-                    }
+        let Some(volume_header) = document.select(&volume_header_selector).next() else {
+            return Vec::new();
+        };
+        
+        for ancestor in volume_header.ancestors().filter_map(ElementRef::wrap) {
+            if ancestor.select(&any_volume_header_selector).count() > 1 {
+                break;
+            }
+            if ancestor.select(&list_chapters_selector).next().is_some() {
+                return ancestor.select(selector).collect();
+            }
+        }
+        
+        // 回退：在该卷header之后的文档区域中查找
+        let mut elements = Vec::new();
+        let mut after_header = false;
+        for node in document.root_element().descendants() {
+            if node.id() == volume_header.id() {
+                after_header = true;
+                continue;
+            }
+            if !after_header {
+                continue;
+            }
+            if let Some(element) = ElementRef::wrap(node) {
+                if any_volume_header_selector.matches(&element) {
+                    break;
+                }
+                if selector.matches(&element) {
+                    elements.push(element);
                 }
             }
         }
+        elements
+    }
+
+    /// 解析不分卷的章节列表（单卷/短篇等没有list-volume的页面）
+    ///
+    /// 收集页面中所有章节列表项，按URL去重。
+    pub fn parse_flat_chapters(&self, document: &Html) -> Vec<Chapter> {
+        let mut chapters: Vec<Chapter> = Vec::new();
+        let chapter_item_selector = Selector::parse("ul.list-chapters li").unwrap();
+        
+        for chapter_item in document.select(&chapter_item_selector) {
+            if let Some(chapter) = self.parse_chapter_item(chapter_item)
+                && !chapters.iter().any(|c| c.url == chapter.url)
+            {
+                chapters.push(chapter);
+            }
+        }
         
         chapters
     }
 
+    /// 解析章节列表中的单个条目
+    fn parse_chapter_item(&self, chapter_item: ElementRef) -> Option<Chapter> {
+        let chapter_name_selector = Selector::parse("div.chapter-name").unwrap();
+        let chapter_link_selector = Selector::parse("a").unwrap();
+        let illustration_icon_selector = Selector::parse("i").unwrap();
+        let chapter_time_selector = Selector::parse("div.chapter-time").unwrap();
+        
+        // 查找章节名称和链接
+        let chapter_name_div = chapter_item.select(&chapter_name_selector).next()?;
+        let chapter_link = chapter_name_div.select(&chapter_link_selector).next()?;
+        
+        let chapter_title = normalize_whitespace(&chapter_link.text().collect::<String>());
+        
+        let chapter_url = chapter_link
+            .value()
+            .attr("href")
+            .unwrap_or("")
+            .to_string();
+        
+        // 检查是否包含插图图标
+        let has_illustrations = chapter_name_div.select(&illustration_icon_selector).next().is_some();
+        
+        // 章节发布时间（部分章节可能没有）
+        let published = chapter_item
+            .select(&chapter_time_selector)
+            .next()
+            .map(|time| normalize_whitespace(&time.text().collect::<String>()))
+            .filter(|time| !time.is_empty());
+        
+        if chapter_title.is_empty() || chapter_url.is_empty() {
+            return None;
+        }
+        
+        Some(Chapter {
+            title: chapter_title,
+            url: chapter_url,
+            has_illustrations,
+            illustration_paths: Vec::new(),
+            has_splash: false,
+            xhtml_path: None,
+            published,
+            char_count: None,
+            word_count: None,
+        })
+    }
+
     pub fn extract_volume_cover_url(&self, document: &Html, volume_id: &str) -> Option<String> {
-        let volume_element_id = volume_id.trim_start_matches('#');
-        let volume_header_selector = Selector::parse(&format!("header#{}", volume_element_id)).unwrap();
         let volume_cover_selector = Selector::parse("div.volume-cover div.content.img-in-ratio").unwrap();
         
-        if let Some(volume_header) = document.select(&volume_header_selector).next() {
-            if let Some(parent_element) = volume_header.parent_element() {
-                if let Some(cover_div) = parent_element.select(&volume_cover_selector).next() {
-                    if let Some(style) = cover_div.value().attr("style") {
-                        // 从style属性中提取URL: background-image: url('...')
-                        if let Some(start) = style.find("url('") {
-                            let start = start + 5; // 跳过 "url('"
-                            if let Some(end) = style[start..].find("')") {
-                                let image_url = &style[start..start + end];
-                                return Some(image_url.to_string());
-                            }
-                        }
-                    }
-                }
-            }
+        let cover_div = self.volume_elements(document, volume_id, &volume_cover_selector).into_iter().next()?;
+        css_url(cover_div.value().attr("style")?)
+    }
+
+    /// 用用户提供的映射覆盖章节标题，键可以是章节URL（绝对URL或站内路径）或URL的最后一段（章节ID）
+    ///
+    /// 命中的键记录到matched中，便于调用方对未使用的条目给出警告。
+    pub fn apply_title_overrides(
+        &self,
+        chapters: &mut [Chapter],
+        overrides: &HashMap<String, String>,
+        matched: &mut HashSet<String>,
+    ) {
+        for chapter in chapters.iter_mut() {
+            let chapter_id = chapter.url.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+            // 绝对URL去掉协议和主机后的站内路径，兼容以相对URL为键的旧覆盖文件
+            let path = chapter
+                .url
+                .split_once("://")
+                .and_then(|(_, rest)| rest.find('/').map(|start| &rest[start..]))
+                .unwrap_or("");
+            let key = if overrides.contains_key(&chapter.url) {
+                chapter.url.clone()
+            } else if !path.is_empty() && overrides.contains_key(path) {
+                path.to_string()
+            } else if !chapter_id.is_empty() && overrides.contains_key(chapter_id) {
+                chapter_id.to_string()
+            } else {
+                continue;
+            };
+            chapter.title = overrides[&key].clone();
+            matched.insert(key);
         }
-        None
     }
-}
\ No newline at end of file
+
+    /// 未命中任何章节的覆盖条目（按键排序）
+    pub fn unmatched_title_overrides<'a>(&self, overrides: &'a HashMap<String, String>, matched: &HashSet<String>) -> Vec<&'a str> {
+        let mut unmatched: Vec<&str> = overrides.keys().filter(|key| !matched.contains(*key)).map(String::as_str).collect();
+        unmatched.sort_unstable();
+        unmatched
+    }
+
+    /// 查找卷章节列表的下一页（分页或“加载更多”）链接
+    pub fn extract_next_chapter_page_url(&self, document: &Html, volume_id: &str) -> Option<String> {
+        let next_page_selector = Selector::parse(
+            "a[rel='next'], .pagination a.next, .pagination-footer a.next, a.load-more, [data-next-page]"
+        ).unwrap();
+
+        let next_element = self.volume_elements(document, volume_id, &next_page_selector).into_iter().next()?;
+        next_element
+            .value()
+            .attr("href")
+            .or_else(|| next_element.value().attr("data-next-page"))
+            .filter(|url| !url.is_empty() && !url.starts_with('#'))
+            .map(|url| url.to_string())
+    }
+}
+
+/// 从style属性中提取URL，兼容 `url('...')`、`url("...")`、`url(...)` 以及括号内外的多余空白
+fn css_url(style: &str) -> Option<String> {
+    let start = style.to_ascii_lowercase().find("url(")? + 4;
+    let end = style[start..].find(')')?;
+    let url = style[start..start + end].trim();
+    let url = url
+        .strip_prefix('\'')
+        .and_then(|url| url.strip_suffix('\''))
+        .or_else(|| url.strip_prefix('"').and_then(|url| url.strip_suffix('"')))
+        .unwrap_or(url)
+        .trim();
+    (!url.is_empty()).then(|| url.to_string())
+}
+
+/// 解析带千位分隔符的计数（如 "1.234.567"、"1,234" 或 "1 234"），忽略所有非数字字符
+fn parse_count(text: &str) -> Option<u64> {
+    let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// 解析评分（如 "4,5 / 5" 或 "8.7"），取斜杠前的部分并兼容逗号作小数点
+fn parse_rating(text: &str) -> Option<f32> {
+    let score = text.split('/').next()?.trim().replace(',', ".");
+    score.parse().ok()
+}
@@ -1,19 +1,360 @@
-use anyhow::Result;
+use crate::error::{DoclnError, Result};
+use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
-use scraper::{Html, Selector};
-use crate::epub::chapter::Chapter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::{JoinError, JoinHandle};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use scraper::{ElementRef, Html, Node, Selector};
+use super::Chapter;
+use crate::epub::{StreamingEpubWriter, DEFAULT_LANGUAGE, IMAGES_DIR};
+use crate::epub::stream::write_work_file;
+use crate::epub::volume::chapter_illustration_paths;
+use crate::utils::{escape_xml, normalize_url, sha256_hex};
+use super::{ImageDownloader, RequestDelay, RequestLimiter, UserAgentRotator};
+use super::http_cache::{CacheValidators, HttpCache};
+use super::content_hashes::ContentHashes;
+use super::footnotes::{self, Footnote};
+use super::{blocked, xhtml};
+use log::{debug, info, warn, error};
 
+/// 章节请求默认的最大重试次数
+pub const DEFAULT_MAX_RETRIES: usize = 3;
+/// 重试间隔按2的幂增长的最大指数，即最长等待64秒
+const MAX_BACKOFF_EXPONENT: usize = 6;
+/// 默认同时处理的章节数
+pub const DEFAULT_CHAPTER_CONCURRENCY: usize = 4;
+/// 默认单个章节内同时下载的插图数
+pub const DEFAULT_IMAGE_CONCURRENCY: usize = 4;
+/// 默认从章节正文中剔除的非内容元素：脚本、样式、嵌入框以及常见的广告容器
+pub const DEFAULT_BLOCKED_SELECTORS: &[&str] = &[
+    "script", "style", "noscript", "ins", "iframe",
+    ".ads", ".adsbygoogle", ".advertisement", "[class^='ad-']", "[class*=' ad-']", "[id^='ad-']",
+];
+/// 章节正文容器的候选选择器，按优先级排列，使用第一个能提取出段落的容器
+pub const CHAPTER_CONTENT_SELECTORS: &[&str] = &[
+    "div#chapter-content", "div.chapter-content", "#chapter-c", "div.reading-content", "article.chapter",
+];
+
+/// 章节开头整页插图的img标签上的class属性，样式见stylesheet.css中的 `img.chapter-splash`
+const SPLASH_CLASS_ATTR: &str = "class=\"chapter-splash\"";
+
+/// 单个章节的处理结果
+#[derive(Debug, Clone)]
+pub struct ProcessedChapter {
+    pub xhtml_path: String, // 相对OEBPS目录的路径
+    pub char_count: usize, // 正文字符数（不含空白）
+    pub word_count: usize, // 正文词数（按空白分隔）
+    pub illustration_paths: Vec<String>, // 成功下载的插图（相对OEBPS目录的路径）
+    pub has_splash: bool, // 第一张插图是否为章节开头的整页插图
+}
+
+/// 单个章节处理完成的事件，由 [`ChapterProcessor::events`] 设置的通道接收
+#[derive(Debug)]
+pub struct ChapterEvent {
+    pub volume_index: usize,
+    pub chapter_index: usize,
+    pub chapter_title: String,
+    pub result: Result<ProcessedChapter>,
+}
+
+/// 章节页面的请求结果
+enum ChapterResponse {
+    Modified { html: String, validators: Option<CacheValidators> },
+    NotModified, // 304，本地XHTML仍是最新
+}
+
+#[derive(Clone)]
 pub struct ChapterProcessor {
     client: reqwest::Client,
     base_url: String,
+    limiter: RequestLimiter,
+    user_agents: UserAgentRotator,
+    request_delay: RequestDelay,
+    max_retries: usize,
+    concurrency: usize,
+    image_concurrency: usize,
+    resume: bool,
+    strip_links: bool,
+    flat_images: bool,
+    inline_illustrations: bool,
+    show_progress: bool,
+    verbose: bool,
+    image_downloader: ImageDownloader,
+    oebps_dir: Option<PathBuf>,
+    http_cache: Option<HttpCache>,
+    content_hashes: Option<ContentHashes>,
+    stream: Option<StreamingEpubWriter>,
+    reuse_chapters: HashSet<usize>,
+    blocked_selectors: Vec<String>,
+    language: String,
+    events: Option<mpsc::UnboundedSender<ChapterEvent>>,
+    image_progress: Option<ProgressBar>, // 当前处理中使用的插图进度条
 }
 
 impl ChapterProcessor {
-    pub fn new(client: reqwest::Client, base_url: String) -> Self {
-        Self { client, base_url }
+    pub fn new(
+        client: reqwest::Client,
+        base_url: String,
+        limiter: RequestLimiter,
+        user_agents: UserAgentRotator,
+        request_delay: impl Into<RequestDelay>,
+    ) -> Self {
+        let image_downloader = ImageDownloader::new(client.clone(), limiter.clone(), user_agents.clone())
+            .base_url(&base_url);
+        Self {
+            client,
+            base_url,
+            limiter,
+            user_agents,
+            request_delay: request_delay.into(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            concurrency: DEFAULT_CHAPTER_CONCURRENCY,
+            image_concurrency: DEFAULT_IMAGE_CONCURRENCY,
+            resume: false,
+            strip_links: false,
+            flat_images: false,
+            inline_illustrations: true,
+            show_progress: false,
+            verbose: true,
+            image_downloader,
+            oebps_dir: None,
+            http_cache: None,
+            content_hashes: None,
+            stream: None,
+            reuse_chapters: HashSet::new(),
+            blocked_selectors: DEFAULT_BLOCKED_SELECTORS.iter().map(|selector| selector.to_string()).collect(),
+            language: DEFAULT_LANGUAGE.to_string(),
+            events: None,
+            image_progress: None,
+        }
+    }
+
+    /// 设置插图允许的最大像素数
+    pub fn max_image_pixels(mut self, max_image_pixels: u64) -> Self {
+        self.image_downloader = self.image_downloader.max_image_pixels(max_image_pixels);
+        self
+    }
+
+    /// 设置插图的缩放和重新编码，None表示保留原图
+    #[cfg(feature = "image-resize")]
+    pub fn image_resize(mut self, image_resize: Option<super::ImageResizeOptions>) -> Self {
+        self.image_downloader = self.image_downloader.image_resize(image_resize);
+        self
+    }
+
+    /// 设置流式写入器：章节XHTML和插图直接写入EPUB而不保存到工作目录
+    pub fn stream(mut self, stream: Option<StreamingEpubWriter>) -> Self {
+        self.image_downloader = self.image_downloader.stream(stream.clone());
+        self.stream = stream;
+        self
+    }
+
+    /// 设置记录ETag/Last-Modified的缓存，已有章节XHTML时发送条件请求，未修改的章节直接复用
+    pub fn http_cache(mut self, http_cache: HttpCache) -> Self {
+        self.http_cache = Some(http_cache);
+        self
+    }
+
+    /// 设置记录章节正文哈希的旁路文件，重新获取的正文与上次相同时复用已有XHTML和插图
+    pub fn content_hashes(mut self, content_hashes: ContentHashes) -> Self {
+        self.content_hashes = Some(content_hashes);
+        self
+    }
+
+    /// 追加一个需要从章节正文中剔除的元素的CSS选择器（如新出现的广告容器）
+    pub fn block_selector(mut self, selector: impl Into<String>) -> Self {
+        self.blocked_selectors.push(selector.into());
+        self
+    }
+
+    /// 替换需要从章节正文中剔除的元素选择器列表，传入空列表则不做过滤
+    pub fn blocked_selectors(mut self, blocked_selectors: Vec<String>) -> Self {
+        self.blocked_selectors = blocked_selectors;
+        self
+    }
+
+    /// 设置是否重新下载已存在的插图
+    pub fn overwrite_images(mut self, overwrite_images: bool) -> Self {
+        self.image_downloader = self.image_downloader.overwrite(overwrite_images);
+        self
+    }
+
+    /// 设置章节请求失败后的最大重试次数
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// 设置同时处理的章节数，设为1即逐章顺序下载
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// 设置单个章节内同时下载的插图数
+    pub fn image_concurrency(mut self, image_concurrency: usize) -> Self {
+        self.image_concurrency = image_concurrency;
+        self
+    }
+
+    /// 设置续传模式：已存在且非空的章节XHTML不再重新下载
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// 设置OEBPS目录，章节XHTML写入其下的text目录；未设置时使用插图目录的上级目录
+    pub fn oebps_dir(mut self, oebps_dir: impl Into<PathBuf>) -> Self {
+        self.oebps_dir = Some(oebps_dir.into());
+        self
+    }
+
+    /// 设置可直接复用已有XHTML的章节索引（增量更新时内容未变化的章节），这些章节不再重新下载
+    pub fn reuse_chapters(mut self, reuse_chapters: HashSet<usize>) -> Self {
+        self.reuse_chapters = reuse_chapters;
+        self
+    }
+
+    /// 设置是否显示章节和插图的下载进度条
+    pub fn show_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    /// 设置是否输出每个章节和文件的处理详情
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self.image_downloader = self.image_downloader.verbose(verbose);
+        self
+    }
+
+    /// 设置是否将章节中的链接去除为纯文本（默认改写为绝对地址）
+    pub fn strip_links(mut self, strip_links: bool) -> Self {
+        self.strip_links = strip_links;
+        self
+    }
+
+    /// 设置接收章节处理事件的通道，每个章节处理完成（成功或失败）后按章节顺序发送一个事件
+    ///
+    /// 续传模式下跳过的章节不发送事件。接收端被关闭时停止处理剩余章节并返回 [`DoclnError::Cancelled`]。
+    pub fn events(mut self, events: mpsc::UnboundedSender<ChapterEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// 设置章节XHTML的语言（xml:lang），默认为越南语 vi
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = language.into();
+        self
+    }
+
+    /// 设置插图扁平存放：全部写入 images/ 并以 vXXX_cXXX_iNNN 命名，避免层层嵌套的目录
+    pub fn flat_images(mut self, flat_images: bool) -> Self {
+        self.flat_images = flat_images;
+        self
+    }
+
+    /// 设置插图是否保留在正文中，默认保留；关闭时插图仍会下载并记录，只从正文中移除（配合插图集使用）
+    pub fn inline_illustrations(mut self, inline_illustrations: bool) -> Self {
+        self.inline_illustrations = inline_illustrations;
+        self
+    }
+
+    /// 从章节页面HTML中提取正文段落（正文容器下的 `p` 元素），没有正文容器时返回空列表
+    ///
+    /// 正文容器依次尝试 [`CHAPTER_CONTENT_SELECTORS`]，如 `div#chapter-content`、`div.chapter-content`。
+    ///
+    /// 使用 [`DEFAULT_BLOCKED_SELECTORS`] 剔除脚本和广告等非内容元素。
+    pub fn parse_chapter_paragraphs(html: &str) -> Vec<String> {
+        let blocked_selectors: Vec<String> = DEFAULT_BLOCKED_SELECTORS.iter().map(|selector| selector.to_string()).collect();
+        Self::parse_chapter_paragraphs_filtered(html, &blocked_selectors)
+    }
+
+    /// 章节是否以插图开头：第一张图片之前没有任何正文文字时，该图片作为章节开头的整页插图处理
+    pub fn starts_with_image(chapter_paragraphs: &[String]) -> bool {
+        for p_html in chapter_paragraphs {
+            let fragment = Html::parse_fragment(p_html);
+            for node in fragment.root_element().descendants() {
+                match node.value() {
+                    Node::Text(text) if !text.trim().is_empty() => return false,
+                    Node::Element(element) if element.name() == "img" && image_source(element).is_some() => return true,
+                    _ => {}
+                }
+            }
+        }
+        false
+    }
+
+    /// 提取正文段落，剔除匹配blocked_selectors的元素
+    ///
+    /// 位于被剔除元素内部的段落整段丢弃，段落内部的被剔除元素单独移除；
+    /// 因此变得既没有文字也没有图片的段落不再保留。无法解析的选择器会被忽略。
+    pub fn parse_chapter_paragraphs_filtered(html: &str, blocked_selectors: &[String]) -> Vec<String> {
+        let document = Html::parse_document(html);
+        CHAPTER_CONTENT_SELECTORS
+            .iter()
+            .map(|selector| Selector::parse(selector).unwrap())
+            .filter_map(|selector| document.select(&selector).next())
+            .map(|content_div| container_paragraphs(content_div, blocked_selectors))
+            .find(|paragraphs| !paragraphs.is_empty())
+            .unwrap_or_default()
     }
 
+    /// 将章节标题、发布时间、正文段落和脚注组装为完整的XHTML文档，段落会规范化为合法的XHTML
+    pub fn build_chapter_xhtml(chapter_title: &str, published: Option<&str>, paragraphs: &[String], footnotes: &[Footnote], language: &str) -> String {
+        // 逐段规范化为合法的XHTML（空元素自闭合、标签闭合、合并空白），段落之间保留换行
+        let content = paragraphs
+            .iter()
+            .map(|p_html| xhtml::normalize_xhtml(p_html))
+            .collect::<Vec<_>>()
+            .join("\n");
+        
+        // 创建XHTML内容 - 在body下创建div容器
+        let mut xhtml_content = String::new();
+        
+        // XHTML头部
+        xhtml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang=""#);
+        xhtml_content.push_str(&escape_xml(language));
+        xhtml_content.push_str(r#"">
+<head>
+    <title>"#);
+        xhtml_content.push_str(&escape_xml(chapter_title));
+        xhtml_content.push_str(r#"</title>
+    <meta http-equiv="Content-Type" content="text/html; charset=UTF-8"/>
+    <link rel="stylesheet" type="text/css" href="../../styles/stylesheet.css"/>
+</head>
+<body>
+    <h1>"#);
+        xhtml_content.push_str(&escape_xml(chapter_title));
+        xhtml_content.push_str("</h1>\n");
+        if let Some(published) = published {
+            xhtml_content.push_str(&format!("    <p class=\"chapter-date\">{}</p>\n", escape_xml(published)));
+        }
+        xhtml_content.push_str(r#"    <div class="chapter-content">
+"#);
+        
+        // 添加章节内容
+        xhtml_content.push_str(&content);
+        xhtml_content.push_str("\n    </div>\n");
+        
+        // 章节末尾的脚注区
+        xhtml_content.push_str(&footnotes::footnotes_xhtml(footnotes));
+        
+        // XHTML尾部
+        xhtml_content.push_str(r#"</body>
+</html>"#);
+        xhtml_content
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn fetch_chapter_content(
         &self,
         chapter_url: &str,
@@ -21,32 +362,68 @@ impl ChapterProcessor {
         chapter_index: usize,
         volume_title: &str,
         chapter_title: &str,
+        published: Option<&str>,
         images_dir: &Path,
         has_illustrations: bool,
-    ) -> Result<String> {
-        println!("正在获取章节内容: {}", chapter_url);
+    ) -> Result<ProcessedChapter> {
+        if self.verbose {
+            info!("正在获取章节内容: {}", chapter_url);
+        }
         
-        let response = self.client.get(chapter_url).send().await?;
-        let html_content = response.text().await?;
+        // 本地已有该章节时附带缓存的校验信息发起条件请求
+        let relative_path = format!("text/volume_{:03}/chapter_{:03}.xhtml", volume_index + 1, chapter_index + 1);
+        let oebps_dir = self.resolve_oebps_dir(images_dir)?;
+        let existing_path = oebps_dir.join(&relative_path);
+        let cached_validators = self.http_cache.as_ref()
+            .filter(|_| fs::metadata(&existing_path).map(|m| m.len() > 0).unwrap_or(false))
+            .and_then(|http_cache| http_cache.get(chapter_url));
         
-        let document = Html::parse_document(&html_content);
+        let (html_content, validators) = match self.fetch_chapter_html(chapter_url, cached_validators.as_ref()).await? {
+            ChapterResponse::Modified { html, validators } => (html, validators),
+            ChapterResponse::NotModified => {
+                if self.verbose {
+                    info!("章节未修改，复用已有内容: {}", chapter_url);
+                }
+                return reuse_existing_chapter(&existing_path, relative_path, volume_index, chapter_index, images_dir, has_illustrations);
+            }
+        };
         
-        // 提取章节内容
-        let chapter_content_selector = Selector::parse("div#chapter-content").unwrap();
-        let mut chapter_paragraphs = Vec::new();
+        // 提取章节段落，将注释标记转为脚注引用后再改写链接，同时统计字数
+        let chapter_paragraphs = Self::parse_chapter_paragraphs_filtered(&html_content, &self.blocked_selectors);
+        if chapter_paragraphs.is_empty() {
+            warn!("警告: 章节 '{}' 未找到正文内容，页面结构可能已变化: {}", chapter_title, chapter_url);
+        }
+        let (chapter_paragraphs, footnotes) = footnotes::apply_footnotes(&html_content, chapter_paragraphs);
+        let chapter_paragraphs: Vec<String> = chapter_paragraphs
+            .iter()
+            .map(|p_html| self.rewrite_links(p_html, chapter_url))
+            .collect();
+        let (char_count, word_count) = paragraph_text_counts(&chapter_paragraphs);
         
-        if let Some(content_div) = document.select(&chapter_content_selector).next() {
-            // 获取所有段落
-            let p_selector = Selector::parse("p").unwrap();
-            for p_element in content_div.select(&p_selector) {
-                chapter_paragraphs.push(p_element.html());
+        // 正文与上次相同且本地已有XHTML时不再重写文件，也不重新下载插图
+        let content_hash = self.content_hashes.as_ref().map(|_| {
+            chapter_content_hash(chapter_title, published, &self.language, &chapter_paragraphs, &footnotes)
+        });
+        if let Some(content_hashes) = &self.content_hashes
+            && content_hashes.get(chapter_url) == content_hash
+            && fs::metadata(&existing_path).map(|m| m.len() > 0).unwrap_or(false)
+        {
+            if self.verbose {
+                info!("章节内容未变化，复用已有内容: {}", chapter_url);
             }
+            if let Some(http_cache) = &self.http_cache
+                && let Some(validators) = validators
+            {
+                http_cache.insert(chapter_url, validators);
+            }
+            return reuse_existing_chapter(&existing_path, relative_path, volume_index, chapter_index, images_dir, has_illustrations);
         }
         
         // 根据章节是否有插图决定是否处理图片
-        let modified_content = if has_illustrations {
+        let (modified_paragraphs, illustration_paths, has_splash) = if has_illustrations {
             self.download_chapter_illustrations(
                 &chapter_paragraphs,
+                chapter_url,
                 images_dir,
                 chapter_index,
                 volume_index,
@@ -55,151 +432,416 @@ impl ChapterProcessor {
             ).await?
         } else {
             // 没有插图，直接使用原始段落内容
-            chapter_paragraphs.join("\n")
+            (chapter_paragraphs, Vec::new(), false)
         };
         
-        // 创建XHTML内容 - 在body下创建div容器
-        let mut xhtml_content = String::new();
-        
-        // XHTML头部
-        xhtml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
-<html xmlns="http://www.w3.org/1999/xhtml">
-<head>
-    <title>"#);
-        xhtml_content.push_str(chapter_title);
-        xhtml_content.push_str(r#"</title>
-    <meta http-equiv="Content-Type" content="text/html; charset=UTF-8"/>
-</head>
-<body>
-    <h1>"#);
-        xhtml_content.push_str(chapter_title);
-        xhtml_content.push_str(r#"</h1>
-    <div class="chapter-content">
-"#);
-        
-        // 添加章节内容
-        xhtml_content.push_str(&modified_content);
-        
-        // XHTML尾部
-        xhtml_content.push_str(r#"    </div>
-</body>
-</html>"#);
+        let xhtml_content = Self::build_chapter_xhtml(chapter_title, published, &modified_paragraphs, &footnotes, &self.language);
         
         // 保存XHTML文件 - 按卷文件夹组织
-        let volume_dir = images_dir.parent().unwrap().join("text").join(format!("volume_{:03}", volume_index + 1));
-        fs::create_dir_all(&volume_dir)?;
-        
+        let volume_dir = oebps_dir.join("text").join(format!("volume_{:03}", volume_index + 1));
         let xhtml_filename = format!("chapter_{:03}.xhtml", chapter_index + 1);
         let xhtml_path = volume_dir.join(&xhtml_filename);
-        fs::write(&xhtml_path, xhtml_content)?;
+        write_work_file(self.stream.as_ref(), &xhtml_path, xhtml_content.as_bytes())?;
         
-        println!("章节 XHTML 已保存到: {}", xhtml_path.display());
+        if self.verbose {
+            debug!("章节 XHTML 已保存到: {}", xhtml_path.display());
+        }
+        
+        // 章节保存成功后才记录校验信息，避免下次对不存在的文件发送条件请求
+        if let Some(http_cache) = &self.http_cache
+            && let Some(validators) = validators
+        {
+            http_cache.insert(chapter_url, validators);
+        }
+        if let Some(content_hashes) = &self.content_hashes
+            && let Some(content_hash) = content_hash
+        {
+            content_hashes.insert(chapter_url, content_hash);
+        }
         
         // 返回相对路径（相对于OEBPS目录）
-        Ok(format!("text/volume_{:03}/{}", volume_index + 1, xhtml_filename))
+        Ok(ProcessedChapter {
+            xhtml_path: format!("text/volume_{:03}/{}", volume_index + 1, xhtml_filename),
+            char_count,
+            word_count,
+            illustration_paths,
+            has_splash,
+        })
+    }
+
+    /// 章节XHTML所在的OEBPS目录：优先使用设置的目录，否则取插图目录的上级目录
+    fn resolve_oebps_dir(&self, images_dir: &Path) -> Result<PathBuf> {
+        if let Some(oebps_dir) = &self.oebps_dir {
+            return Ok(oebps_dir.clone());
+        }
+        images_dir.parent().map(Path::to_path_buf).ok_or_else(|| {
+            DoclnError::InvalidInput(format!("无法确定OEBPS目录: 插图目录 {} 没有上级目录，请通过 oebps_dir 指定", images_dir.display()))
+        })
+    }
+
+    /// 获取章节页面，对超时、连接错误、5xx和429按指数退避重试（1s、2s、4s…），其他4xx不重试
+    ///
+    /// 被限流或遇到Cloudflare验证页面时返回对应的错误，而不是把验证页面当作章节内容。
+    async fn fetch_chapter_html(&self, chapter_url: &str, cached_validators: Option<&CacheValidators>) -> Result<ChapterResponse> {
+        let mut attempt = 0;
+        loop {
+            let result: Result<ChapterResponse> = async {
+                let _permit = self.limiter.acquire_chapter().await;
+                let mut request = self.user_agents.apply(self.client.get(chapter_url));
+                if let Some(cached_validators) = cached_validators {
+                    request = cached_validators.apply(request);
+                }
+                let response = request.send().await?;
+                let status = response.status();
+                let final_url = response.url().clone();
+                if status == reqwest::StatusCode::NOT_MODIFIED {
+                    return Ok(ChapterResponse::NotModified);
+                }
+                let validators = CacheValidators::from_headers(response.headers());
+                let retry_after = blocked::retry_after(response.headers());
+                let status_error = response.error_for_status_ref().err();
+                let html_content = response.text().await?;
+                if let Some(blocked) = blocked::detect_block(status, retry_after, &html_content) {
+                    return Err(blocked);
+                }
+                if blocked::login_required(status, &final_url, &html_content) {
+                    return Err(DoclnError::LoginRequired(chapter_url.to_string()));
+                }
+                if let Some(e) = status_error {
+                    return Err(e.into());
+                }
+                Ok(ChapterResponse::Modified { html: html_content, validators })
+            }.await;
+
+            let error = match result {
+                Ok(chapter_response) => return Ok(chapter_response),
+                Err(e) => e,
+            };
+
+            // 被限流时可以等待后重试，Cloudflare验证页面重试无济于事
+            let retryable = match &error {
+                DoclnError::RateLimited { .. } => true,
+                DoclnError::Network(e) => e.status().is_none_or(|status| status.is_server_error()),
+                _ => false,
+            };
+            if !retryable || attempt >= self.max_retries {
+                return Err(error);
+            }
+
+            // 指数退避，间隔最长为2的MAX_BACKOFF_EXPONENT次方秒；服务器要求的Retry-After更长时以其为准
+            let mut backoff = Duration::from_secs(1u64 << attempt.min(MAX_BACKOFF_EXPONENT));
+            if let DoclnError::RateLimited { retry_after: Some(retry_after) } = &error {
+                backoff = backoff.max(*retry_after);
+            }
+            attempt += 1;
+            warn!("获取章节失败: {}，{}秒后进行第 {} 次重试", error, backoff.as_secs(), attempt);
+            tokio::time::sleep(backoff).await;
+        }
     }
 
     pub async fn fetch_and_process_chapters(
         &self,
-        chapters: &mut Vec<Chapter>,
+        chapters: &mut [Chapter],
         volume_index: usize,
         volume_title: &str,
         _novel_title: &str,
         images_dir: &Path,
     ) -> Result<()> {
-        println!("\n正在处理卷 '{}' 的章节内容...", volume_title);
+        info!("正在处理卷 '{}' 的章节内容...", volume_title);
+        let oebps_dir = self.resolve_oebps_dir(images_dir)?;
+        
+        // 每个章节一个任务，由信号量限制同时处理的章节数
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
+        let mut handles = Vec::with_capacity(chapters.len());
         
+        // 进度条：章节进度以及插图计数
+        let multi_progress = MultiProgress::new();
+        let (chapter_bar, image_bar) = if self.show_progress {
+            let chapter_bar = multi_progress.add(ProgressBar::new(chapters.len() as u64));
+            chapter_bar.set_style(
+                ProgressStyle::with_template("{prefix} [{bar:40}] 章节 {pos}/{len} {msg}")
+                    .unwrap()
+                    .progress_chars("=> "),
+            );
+            chapter_bar.set_prefix(volume_title.to_string());
+            let image_bar = multi_progress.add(ProgressBar::new(0));
+            image_bar.set_style(ProgressStyle::with_template("{prefix} 插图 {pos}/{len}").unwrap());
+            image_bar.set_prefix(volume_title.to_string());
+            (Some(chapter_bar), Some(image_bar))
+        } else {
+            (None, None)
+        };
+        
+        for (chapter_index, chapter) in chapters.iter().enumerate() {
+            if self.reuse_chapters.contains(&chapter_index) {
+                handles.push(None);
+                continue;
+            }
+            
+            // 续传模式下跳过已下载的章节
+            if self.resume {
+                let relative_path = format!("text/volume_{:03}/chapter_{:03}.xhtml", volume_index + 1, chapter_index + 1);
+                if fs::metadata(oebps_dir.join(&relative_path)).map(|m| m.len() > 0).unwrap_or(false) {
+                    handles.push(None);
+                    continue;
+                }
+            }
+            
+            let full_chapter_url = normalize_url(&chapter.url, &self.base_url);
+            
+            let mut processor = self.clone();
+            processor.image_progress = image_bar.clone();
+            let chapter_bar = chapter_bar.clone();
+            let semaphore = semaphore.clone();
+            let volume_title = volume_title.to_string();
+            let chapter_title = chapter.title.clone();
+            let published = chapter.published.clone();
+            let images_dir = images_dir.to_path_buf();
+            let has_illustrations = chapter.has_illustrations;
+            
+            handles.push(Some(AbortOnDrop(tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+                let result = processor.fetch_chapter_content(
+                    &full_chapter_url,
+                    volume_index,
+                    chapter_index,
+                    &volume_title,
+                    &chapter_title,
+                    published.as_deref(),
+                    &images_dir,
+                    has_illustrations,
+                ).await;
+                
+                if let Some(chapter_bar) = &chapter_bar {
+                    chapter_bar.set_message(chapter_title);
+                    chapter_bar.inc(1);
+                }
+                
+                // 添加短暂的随机延迟，避免请求过快且间隔过于规律
+                let delay = processor.request_delay.sample();
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                result
+            }))));
+        }
+        
+        // 按索引写回结果，保持章节顺序
+        let mut handles = handles.into_iter();
         for (chapter_index, chapter) in chapters.iter_mut().enumerate() {
-            let full_chapter_url = if chapter.url.starts_with("/") {
-                format!("{}{}", self.base_url, chapter.url)
-            } else {
-                chapter.url.clone()
+            let Some(handle) = handles.next().flatten() else {
+                chapter.xhtml_path = Some(format!("text/volume_{:03}/chapter_{:03}.xhtml", volume_index + 1, chapter_index + 1));
+                // 跳过的章节没有本次下载记录，从磁盘上找回之前下载的插图
+                if chapter.has_illustrations {
+                    chapter.illustration_paths = chapter_illustration_paths(volume_index, chapter_index, images_dir);
+                    chapter.has_splash = fs::read_to_string(oebps_dir.join(chapter.xhtml_path.as_deref().unwrap_or_default()))
+                        .is_ok_and(|content| content.contains(SPLASH_CLASS_ATTR));
+                }
+                if let Some(chapter_bar) = &chapter_bar {
+                    chapter_bar.inc(1);
+                }
+                if self.verbose {
+                    info!("  章节 '{}': 已存在，跳过", chapter.title);
+                }
+                continue;
             };
-            
-            match self.fetch_chapter_content(
-                &full_chapter_url,
-                volume_index,
-                chapter_index,
-                volume_title,
-                &chapter.title,
-                images_dir,
-                chapter.has_illustrations,
-            ).await {
-                Ok(xhtml_path) => {
-                    chapter.xhtml_path = Some(xhtml_path);
-                    println!("  章节 '{}': 已处理", chapter.title);
+            let result = match handle.await {
+                Ok(result) => result,
+                Err(e) => {
+                    error!("  章节 '{}' 处理任务异常: {}", chapter.title, e);
+                    continue;
+                }
+            };
+            match &result {
+                Ok(processed) => {
+                    chapter.xhtml_path = Some(processed.xhtml_path.clone());
+                    chapter.char_count = Some(processed.char_count);
+                    chapter.word_count = Some(processed.word_count);
+                    chapter.illustration_paths = processed.illustration_paths.clone();
+                    chapter.has_splash = processed.has_splash;
+                    if self.verbose {
+                        info!("  章节 '{}': 已处理", chapter.title);
+                    }
                 },
+                Err(DoclnError::LoginRequired(_)) => {
+                    warn!("  章节 '{}' 仅限登录会员阅读，已跳过（可通过cookie提供登录会话）", chapter.title);
+                }
                 Err(e) => {
-                    println!("  章节 '{}' 处理失败: {}", chapter.title, e);
+                    error!("  章节 '{}' 处理失败: {}", chapter.title, e);
                     // 继续处理其他章节
                 }
             }
             
-            // 添加短暂延迟，避免请求过快
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            if let Some(events) = &self.events {
+                let event = ChapterEvent {
+                    volume_index,
+                    chapter_index,
+                    chapter_title: chapter.title.clone(),
+                    result,
+                };
+                if events.send(event).is_err() {
+                    // 接收端已关闭，取消尚未完成的章节
+                    for handle in handles.by_ref().flatten() {
+                        handle.abort();
+                    }
+                    if let Some(chapter_bar) = &chapter_bar {
+                        chapter_bar.abandon();
+                    }
+                    info!("章节事件的接收端已关闭，停止处理卷 '{}' 的剩余章节", volume_title);
+                    return Err(DoclnError::Cancelled);
+                }
+            }
+        }
+        
+        if let Some(chapter_bar) = chapter_bar {
+            chapter_bar.finish();
+        }
+        if let Some(image_bar) = image_bar {
+            image_bar.finish();
         }
         
         Ok(())
     }
 
+    /// 处理段落中的链接：相对地址改写为基于章节URL的绝对地址，或去除为纯文本
+    fn rewrite_links(&self, p_html: &str, chapter_url: &str) -> String {
+        let p_document = Html::parse_fragment(p_html);
+        let link_selector = Selector::parse("a[href]").unwrap();
+        let base = reqwest::Url::parse(chapter_url).ok();
+        let mut modified_p_html = p_html.to_string();
+
+        for link_element in p_document.select(&link_selector) {
+            let original_link_html = link_element.html();
+            let href = link_element.value().attr("href").unwrap_or("");
+
+            // 页内锚点（包括脚注引用）保持不变
+            if href.starts_with('#') {
+                continue;
+            }
+            
+            let modified_link_html = if self.strip_links {
+                link_element.inner_html()
+            } else {
+                let Some(absolute_url) = base.as_ref().and_then(|base| base.join(href).ok()) else {
+                    continue;
+                };
+                original_link_html.replacen(
+                    &format!("href=\"{}\"", serialized_attr_value(href)),
+                    &format!("href=\"{}\"", serialized_attr_value(absolute_url.as_str())),
+                    1,
+                )
+            };
+            modified_p_html = modified_p_html.replacen(&original_link_html, &modified_link_html, 1);
+        }
+
+        modified_p_html
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn download_chapter_illustrations(
         &self,
         chapter_paragraphs: &[String],
+        chapter_url: &str,
         images_dir: &Path,
         chapter_index: usize,
         volume_index: usize,
         _volume_title: &str,
         _chapter_title: &str,
-    ) -> Result<String> {
-        let mut modified_paragraphs = Vec::new();
-        let mut illustration_counter = 1;
-        
-        // 直接创建插图目录 - 按卷文件夹组织（因为进入这个函数的章节一定有插图）
-        let volume_img_dir = images_dir.join(format!("volume_{:03}", volume_index + 1));
-        let chapter_img_dir = volume_img_dir.join(format!("chapter_{:03}", chapter_index + 1));
-        fs::create_dir_all(&chapter_img_dir)?;
-        let illustrations_dir = Some(chapter_img_dir);
+    ) -> Result<(Vec<String>, Vec<String>, bool)> {
+        // 直接创建插图目录 - 默认按卷/章节文件夹组织，扁平存放时直接写入images（因为进入这个函数的章节一定有插图）
+        let chapter_img_dir = if self.flat_images {
+            images_dir.to_path_buf()
+        } else {
+            images_dir
+                .join(format!("volume_{:03}", volume_index + 1))
+                .join(format!("chapter_{:03}", chapter_index + 1))
+        };
+        if self.stream.is_none() {
+            fs::create_dir_all(&chapter_img_dir)?;
+        }
         
-        // 处理每个段落
-        for p_html in chapter_paragraphs {
-            let mut modified_p_html = p_html.clone();
-            
-            // 解析段落HTML来查找图片
-            let p_document = Html::parse_fragment(&p_html);
+        // 先收集整章的图片 (段落索引, 原始img标签, 图片地址, 替代文本)，按文档顺序编号
+        let images: Vec<(usize, String, String, String)> = {
             let img_selector = Selector::parse("img").unwrap();
-            
-            // 处理段落中的图片（如果有）
-            for img_element in p_document.select(&img_selector) {
-                if let Some(img_src) = img_element.value().attr("src") {
-                    if !img_src.is_empty() {
-                        // 下载图片
-                        match self.download_illustration(img_src, illustrations_dir.as_ref().unwrap(), illustration_counter, volume_index, chapter_index).await {
-                            Ok(local_path) => {
-                                // 替换原始src为本地路径（相对于images目录）
-                                let original_img_html = img_element.html();
-                                // 确保img标签正确闭合
-                                let modified_img_html = if original_img_html.ends_with("/>") {
-                                    original_img_html.replace(img_src, &local_path)
-                                } else {
-                                    original_img_html.replace(img_src, &local_path).replace(">", "/>")
-                                };
-                                modified_p_html = modified_p_html.replace(&original_img_html, &modified_img_html);
-                                
-                                illustration_counter += 1;
-                            },
-                            Err(e) => {
-                                println!("下载插图失败: {}", e);
-                            }
-                        }
+            let mut images = Vec::new();
+            for (paragraph_index, p_html) in chapter_paragraphs.iter().enumerate() {
+                let p_document = Html::parse_fragment(p_html);
+                for img_element in p_document.select(&img_selector) {
+                    if let Some(img_src) = image_source(img_element.value()) {
+                        // 相对地址按章节页面解析
+                        let alt = img_element.value().attr("alt").unwrap_or("").to_string();
+                        images.push((paragraph_index, img_element.html(), normalize_url(&img_src, chapter_url), alt));
                     }
                 }
             }
-            
-            modified_paragraphs.push(modified_p_html);
+            images
+        };
+        
+        if let Some(image_bar) = &self.image_progress {
+            image_bar.inc_length(images.len() as u64);
+        }
+        
+        // 并发下载，编号在启动任务前确定，保证文件名稳定
+        let semaphore = Arc::new(Semaphore::new(self.image_concurrency.max(1)));
+        let mut handles = Vec::with_capacity(images.len());
+        for (image_index, (_, _, img_src, _)) in images.iter().enumerate() {
+            let processor = self.clone();
+            let semaphore = semaphore.clone();
+            let img_src = img_src.clone();
+            let chapter_img_dir = chapter_img_dir.clone();
+            handles.push(AbortOnDrop(tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await.expect("semaphore closed");
+                let result = processor
+                    .download_illustration(&img_src, &chapter_img_dir, image_index + 1, volume_index, chapter_index)
+                    .await;
+                if result.is_ok()
+                    && let Some(image_bar) = &processor.image_progress
+                {
+                    image_bar.inc(1);
+                }
+                result
+            })));
+        }
+        
+        // 按文档顺序替换为本地路径，并记录下载成功的插图；正文之前的第一张图片标记为整页插图
+        // 不在正文中保留插图时，下载成功的图片从段落中移除，因此变空的段落一并丢弃
+        let starts_with_image = self.inline_illustrations && Self::starts_with_image(chapter_paragraphs);
+        let mut modified_paragraphs = chapter_paragraphs.to_vec();
+        let mut illustration_paths = Vec::new();
+        let mut has_splash = false;
+        let mut emptied_paragraphs = HashSet::new();
+        for (image_index, ((paragraph_index, original_img_html, _, alt), handle)) in images.into_iter().zip(handles).enumerate() {
+            match handle.await {
+                Ok(Ok(image_path)) => {
+                    // 用指向本地文件的自闭合img标签替换原始标签，丢弃懒加载属性（章节位于text/volume_XXX/下）
+                    let splash = image_index == 0 && starts_with_image;
+                    let modified_img_html = format!(
+                        "<img src=\"../../{}\" alt=\"{}\"{}/>",
+                        image_path,
+                        escape_xml(&alt),
+                        if splash { format!(" {}", SPLASH_CLASS_ATTR) } else { String::new() }
+                    );
+                    has_splash |= splash;
+                    // 只替换第一处：同一段中相同的img标签各自对应一次下载，按顺序依次替换
+                    let replacement = if self.inline_illustrations { modified_img_html.as_str() } else { "" };
+                    modified_paragraphs[paragraph_index] = modified_paragraphs[paragraph_index].replacen(&original_img_html, replacement, 1);
+                    if !self.inline_illustrations {
+                        emptied_paragraphs.insert(paragraph_index);
+                    }
+                    illustration_paths.push(image_path);
+                }
+                Ok(Err(e)) => warn!("下载插图失败: {}", e),
+                Err(e) => warn!("下载插图任务异常: {}", e),
+            }
         }
         
-        Ok(modified_paragraphs.join("\n"))
+        let modified_paragraphs = modified_paragraphs
+            .into_iter()
+            .enumerate()
+            .filter(|(paragraph_index, p_html)| !(emptied_paragraphs.contains(paragraph_index) && is_blank_paragraph(p_html)))
+            .map(|(_, p_html)| p_html)
+            .collect();
+        Ok((modified_paragraphs, illustration_paths, has_splash))
     }
 
     async fn download_illustration(
@@ -210,24 +852,175 @@ impl ChapterProcessor {
         volume_index: usize,
         chapter_index: usize,
     ) -> Result<String> {
-        // 从URL中提取文件扩展名
-        let extension = std::path::Path::new(image_url)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("jpg");
-        
-        // 插图命名为顺序编号
-        let filename = format!("{:03}.{}", illustration_number, extension);
-        let filepath = illustrations_dir.join(&filename);
+        // 插图命名为顺序编号，扩展名由图片实际格式决定；扁平存放时加上卷和章节编号保证全局唯一
+        let file_stem = if self.flat_images {
+            format!("v{:03}_c{:03}_i{:03}", volume_index + 1, chapter_index + 1, illustration_number)
+        } else {
+            format!("{:03}", illustration_number)
+        };
         
         // 使用通用函数下载图片
-        println!("正在下载插图 {}: {}", illustration_number, image_url);
-        let response = self.client.get(image_url).send().await?;
-        let image_bytes = response.bytes().await?;
-        std::fs::write(&filepath, &image_bytes)?;
-        println!("插图 {} 已保存到: {}", illustration_number, filepath.display());
+        let filename = self.image_downloader
+            .download_image(image_url, illustrations_dir, &file_stem, &format!("插图 {} ", illustration_number))
+            .await?;
         
-        // 返回正确的相对路径（从text/volume_XXX/chapter_XXX.xhtml到images/volume_XXX/chapter_XXX/）
-        Ok(format!("../../images/volume_{:03}/chapter_{:03}/{}", volume_index + 1, chapter_index + 1, filename))
+        // 返回相对OEBPS目录的路径
+        if self.flat_images {
+            Ok(format!("{}/{}", IMAGES_DIR, filename))
+        } else {
+            Ok(format!("{}/volume_{:03}/chapter_{:03}/{}", IMAGES_DIR, volume_index + 1, chapter_index + 1, filename))
+        }
+    }
+}
+
+/// 从一个正文容器中提取段落，剔除匹配blocked_selectors的元素
+fn container_paragraphs(content_div: ElementRef, blocked_selectors: &[String]) -> Vec<String> {
+    let p_selector = Selector::parse("p").unwrap();
+    
+    // 收集正文中所有需要剔除的元素
+    let blocked: Vec<ElementRef> = blocked_selectors
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .flat_map(|selector| content_div.select(&selector).collect::<Vec<_>>())
+        .collect();
+    let is_blocked = |element: &ElementRef| blocked.iter().any(|blocked_element| blocked_element.id() == element.id());
+
+    let mut paragraphs = Vec::new();
+    for p_element in content_div.select(&p_selector) {
+        // 段落本身或其祖先被剔除时整段丢弃
+        let inside_blocked = std::iter::successors(Some(p_element), |element| element.parent().and_then(ElementRef::wrap))
+            .take_while(|element| element.id() != content_div.id())
+            .any(|element| is_blocked(&element));
+        if inside_blocked {
+            continue;
+        }
+
+        let mut p_html = p_element.html();
+        let mut removed = false;
+        for descendant in p_element.descendants().filter_map(ElementRef::wrap).skip(1) {
+            if is_blocked(&descendant) {
+                p_html = p_html.replacen(&descendant.html(), "", 1);
+                removed = true;
+            }
+        }
+
+        // 只丢弃因剔除而变空的段落，原有的空行保持不变
+        if removed && is_blank_paragraph(&p_html) {
+            continue;
+        }
+        paragraphs.push(p_html);
+    }
+    paragraphs
+}
+
+/// 段落既没有文字也没有图片
+fn is_blank_paragraph(p_html: &str) -> bool {
+    let fragment = Html::parse_fragment(p_html);
+    let img_selector = Selector::parse("img").unwrap();
+    fragment.root_element().text().all(|text| text.trim().is_empty()) && fragment.select(&img_selector).next().is_none()
+}
+
+/// 统计段落正文的字符数（不含空白）和词数（按空白分隔）
+fn paragraph_text_counts(paragraphs: &[String]) -> (usize, usize) {
+    let mut char_count = 0;
+    let mut word_count = 0;
+    for p_html in paragraphs {
+        let text = Html::parse_fragment(p_html).root_element().text().collect::<String>();
+        char_count += text.chars().filter(|c| !c.is_whitespace()).count();
+        word_count += text.split_whitespace().count();
+    }
+    (char_count, word_count)
+}
+
+/// 章节内容的哈希：标题、发布时间、语言、正文段落（含改写后的链接和原始图片地址）和脚注，任一变化都需要重写XHTML
+fn chapter_content_hash(chapter_title: &str, published: Option<&str>, language: &str, paragraphs: &[String], footnotes: &[Footnote]) -> String {
+    let mut content = format!("{}\n{}\n{}\n", chapter_title, published.unwrap_or_default(), language);
+    for p_html in paragraphs {
+        content.push_str(p_html);
+        content.push('\n');
+    }
+    for footnote in footnotes {
+        content.push_str(&format!("{}. {}\n", footnote.number, footnote.text));
+    }
+    sha256_hex(content.as_bytes())
+}
+
+/// 复用未修改章节的已有XHTML：从文件中重新统计字数，从磁盘找回插图
+fn reuse_existing_chapter(
+    existing_path: &Path,
+    xhtml_path: String,
+    volume_index: usize,
+    chapter_index: usize,
+    images_dir: &Path,
+    has_illustrations: bool,
+) -> Result<ProcessedChapter> {
+    let existing_content = fs::read_to_string(existing_path)?;
+    let paragraphs: Vec<String> = {
+        let document = Html::parse_document(&existing_content);
+        let p_selector = Selector::parse("div.chapter-content p").unwrap();
+        document.select(&p_selector).map(|p_element| p_element.html()).collect()
+    };
+    let (char_count, word_count) = paragraph_text_counts(&paragraphs);
+    
+    let illustration_paths = if has_illustrations {
+        chapter_illustration_paths(volume_index, chapter_index, images_dir)
+    } else {
+        Vec::new()
+    };
+    
+    Ok(ProcessedChapter {
+        xhtml_path,
+        char_count,
+        word_count,
+        illustration_paths,
+        has_splash: has_illustrations && existing_content.contains(SPLASH_CLASS_ATTR),
+    })
+}
+
+/// 丢弃时取消任务的JoinHandle
+///
+/// 章节和插图任务由tokio::spawn启动，外层的卷任务被取消（或调用方丢弃Future）时，
+/// 未完成的任务随句柄一起取消，而不是在后台继续请求和写入。
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> AbortOnDrop<T> {
+    fn abort(&self) {
+        self.0.abort();
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
     }
+}
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = std::result::Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+/// 懒加载图片存放真实地址的属性，优先于src
+const LAZY_IMAGE_ATTRS: &[&str] = &["data-src", "data-original", "data-lazy-src"];
+
+/// 取得图片的真实地址：优先使用懒加载属性，其次src
+fn image_source(img: &scraper::node::Element) -> Option<String> {
+    LAZY_IMAGE_ATTRS
+        .iter()
+        .chain(std::iter::once(&"src"))
+        .filter_map(|attr| img.attr(attr))
+        .map(str::trim)
+        .find(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// 按scraper序列化属性值的方式转义，用于在序列化后的HTML中定位属性
+fn serialized_attr_value(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('\u{a0}', "&nbsp;")
+        .replace('"', "&quot;")
 }
\ No newline at end of file
@@ -0,0 +1,127 @@
+use std::time::Duration;
+use reqwest::Url;
+
+/// 从robots.txt中解析出的、适用于本爬虫的规则
+///
+/// 只取最匹配的一组User-agent规则：名称包含在请求User-Agent中的组优先，其次是 `*` 组。
+/// 路径规则支持 `*` 通配符和结尾的 `$`，Allow与Disallow同时匹配时取规则更长的一方。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RobotsRules {
+    allow: Vec<String>,
+    disallow: Vec<String>,
+    crawl_delay: Option<Duration>,
+}
+
+/// robots.txt中的一组规则
+#[derive(Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: RobotsRules,
+}
+
+impl RobotsRules {
+    /// 解析robots.txt，user_agent为请求时使用的User-Agent
+    pub fn parse(content: &str, user_agent: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        let mut in_agent_lines = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            if key == "user-agent" {
+                // 连续的User-agent行属于同一组
+                if !in_agent_lines {
+                    groups.push(Group::default());
+                }
+                in_agent_lines = true;
+                if let Some(group) = groups.last_mut() {
+                    group.agents.push(value.to_ascii_lowercase());
+                }
+                continue;
+            }
+            in_agent_lines = false;
+            let Some(group) = groups.last_mut() else {
+                continue;
+            };
+            match key.as_str() {
+                "allow" if !value.is_empty() => group.rules.allow.push(value.to_string()),
+                // 空的Disallow表示允许全部
+                "disallow" if !value.is_empty() => group.rules.disallow.push(value.to_string()),
+                "crawl-delay" => {
+                    group.rules.crawl_delay = value.parse::<f64>().ok().filter(|secs| secs.is_finite() && *secs >= 0.0).map(Duration::from_secs_f64);
+                }
+                _ => {}
+            }
+        }
+
+        let user_agent = user_agent.to_ascii_lowercase();
+        let specific = groups
+            .iter()
+            .position(|group| group.agents.iter().any(|agent| agent != "*" && user_agent.contains(agent.as_str())));
+        let wildcard = groups.iter().position(|group| group.agents.iter().any(|agent| agent == "*"));
+        specific
+            .or(wildcard)
+            .map(|index| groups.swap_remove(index).rules)
+            .unwrap_or_default()
+    }
+
+    /// 路径（可带查询字符串）是否允许访问
+    pub fn is_allowed(&self, path: &str) -> bool {
+        let longest_match = |patterns: &[String]| {
+            patterns
+                .iter()
+                .filter(|pattern| pattern_matches(pattern, path))
+                .map(String::len)
+                .max()
+        };
+        match (longest_match(&self.allow), longest_match(&self.disallow)) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(allow), Some(disallow)) => allow >= disallow,
+        }
+    }
+
+    /// 完整URL是否允许访问，无法解析的URL视为允许
+    pub fn is_url_allowed(&self, url: &str) -> bool {
+        let Ok(url) = Url::parse(url) else {
+            return true;
+        };
+        match url.query() {
+            Some(query) => self.is_allowed(&format!("{}?{}", url.path(), query)),
+            None => self.is_allowed(url.path()),
+        }
+    }
+
+    /// Crawl-delay指定的请求间隔
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+/// 按robots.txt的规则匹配路径：`*` 匹配任意字符，结尾的 `$` 要求匹配到路径末尾，否则为前缀匹配
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or("")) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    for (index, part) in parts.iter().enumerate() {
+        // 锚定时最后一段必须出现在路径末尾
+        if anchored && index == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
@@ -0,0 +1,182 @@
+use crate::error::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::epub::{Chapter, Epub};
+use crate::utils::sha256_hex;
+use log::{debug, info, warn};
+
+/// 增量更新时，状态文件中有记录但当前章节列表中已经没有的章节的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RemovedChapters {
+    /// 保留在书中，放回原来的位置（默认）
+    #[default]
+    Keep,
+    /// 从书中移除，并删除不再被占用的章节文件
+    Drop,
+}
+
+/// 一个已下载章节的记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChapterRecord {
+    pub url: String,
+    pub title: String,
+    pub published: Option<String>,
+    pub has_illustrations: bool,
+    pub volume_index: usize,
+    pub chapter_index: usize,
+    pub xhtml_path: String, // 相对OEBPS目录的路径
+    pub content_hash: String, // 章节XHTML文件的SHA-256
+}
+
+/// 与状态文件比较当前章节列表的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncrementalPlan {
+    pub unchanged: Vec<HashSet<usize>>, // 每卷中内容未变化、可直接复用的章节索引
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+}
+
+/// 每本小说一个的增量更新状态文件，记录上次爬取的章节URL、位置和内容哈希
+///
+/// 与基于文件是否存在的续传不同，增量模式按章节URL比较前后两次的章节列表：
+/// 新增、标题或更新时间变化、位置变化以及本地文件与记录不符的章节会重新下载，其余直接复用。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrawlState {
+    pub chapters: Vec<ChapterRecord>,
+}
+
+impl CrawlState {
+    /// 读取状态文件，文件不存在或无法解析时返回None
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content)
+            .map_err(|e| warn!("无法解析状态文件 {}: {}", path.display(), e))
+            .ok()
+    }
+
+    /// 写入状态文件
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        debug!("增量状态已保存到: {}", path.display());
+        Ok(())
+    }
+
+    /// 根据爬取结果生成状态，只记录已成功生成XHTML的章节
+    pub fn from_epub(epub: &Epub, oebps_dir: &Path) -> Self {
+        let mut chapters = Vec::new();
+        for (volume_index, volume) in epub.volumes.iter().enumerate() {
+            for (chapter_index, chapter) in volume.chapters.iter().enumerate() {
+                let Some(xhtml_path) = &chapter.xhtml_path else {
+                    continue;
+                };
+                let Some(content_hash) = file_hash(&oebps_dir.join(xhtml_path)) else {
+                    continue;
+                };
+                chapters.push(ChapterRecord {
+                    url: chapter.url.clone(),
+                    title: chapter.title.clone(),
+                    published: chapter.published.clone(),
+                    has_illustrations: chapter.has_illustrations,
+                    volume_index,
+                    chapter_index,
+                    xhtml_path: xhtml_path.clone(),
+                    content_hash,
+                });
+            }
+        }
+        Self { chapters }
+    }
+
+    /// 将当前章节列表与状态比较，按removed的设置处理已删除的章节，返回可复用的章节
+    pub fn plan(&self, epub: &mut Epub, oebps_dir: &Path, removed: RemovedChapters) -> IncrementalPlan {
+        let current_urls: HashSet<String> = epub
+            .volumes
+            .iter()
+            .flat_map(|volume| &volume.chapters)
+            .map(|chapter| chapter.url.clone())
+            .collect();
+        let mut removed_records: Vec<&ChapterRecord> = self
+            .chapters
+            .iter()
+            .filter(|record| !current_urls.contains(&record.url))
+            .collect();
+        removed_records.sort_by_key(|record| (record.volume_index, record.chapter_index));
+
+        let mut plan = IncrementalPlan {
+            removed: removed_records.len(),
+            ..IncrementalPlan::default()
+        };
+        match removed {
+            RemovedChapters::Keep => {
+                for record in &removed_records {
+                    let Some(volume) = epub.volumes.get_mut(record.volume_index) else {
+                        warn!("警告: 已删除章节 '{}' 所在的卷已不存在，无法保留", record.title);
+                        continue;
+                    };
+                    let chapter = Chapter::builder()
+                        .title(record.title.clone())
+                        .url(record.url.clone())
+                        .has_illustrations(record.has_illustrations)
+                        .published(record.published.clone())
+                        .build();
+                    let chapter_index = record.chapter_index.min(volume.chapters.len());
+                    volume.chapters.insert(chapter_index, chapter);
+                }
+            }
+            RemovedChapters::Drop => {
+                for record in &removed_records {
+                    let occupied = epub
+                        .volumes
+                        .get(record.volume_index)
+                        .is_some_and(|volume| record.chapter_index < volume.chapters.len());
+                    if !occupied && fs::remove_file(oebps_dir.join(&record.xhtml_path)).is_ok() {
+                        debug!("已删除章节 '{}' 的文件: {}", record.title, record.xhtml_path);
+                    }
+                }
+            }
+        }
+
+        let records: HashMap<&str, &ChapterRecord> = self.chapters.iter().map(|record| (record.url.as_str(), record)).collect();
+        for (volume_index, volume) in epub.volumes.iter().enumerate() {
+            let mut unchanged = HashSet::new();
+            for (chapter_index, chapter) in volume.chapters.iter().enumerate() {
+                let Some(record) = records.get(chapter.url.as_str()) else {
+                    plan.added += 1;
+                    continue;
+                };
+                let reusable = record.volume_index == volume_index
+                    && record.chapter_index == chapter_index
+                    && record.title == chapter.title
+                    && record.published == chapter.published
+                    && file_hash(&oebps_dir.join(&record.xhtml_path)).as_ref() == Some(&record.content_hash);
+                if reusable {
+                    unchanged.insert(chapter_index);
+                } else if current_urls.contains(&record.url) {
+                    // 放回的已删除章节已计入删除数，这里只统计仍在章节列表中的
+                    plan.changed += 1;
+                }
+            }
+            plan.unchanged.push(unchanged);
+        }
+
+        info!(
+            "增量更新: 新增 {} 章，变化 {} 章，删除 {} 章（{}），复用 {} 章",
+            plan.added,
+            plan.changed,
+            plan.removed,
+            if removed == RemovedChapters::Keep { "保留" } else { "移除" },
+            plan.unchanged.iter().map(HashSet::len).sum::<usize>()
+        );
+        plan
+    }
+}
+
+fn file_hash(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|content| sha256_hex(&content))
+}
@@ -0,0 +1,38 @@
+use super::Epub;
+use log::info;
+
+/// 一次爬取得到的内容统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CrawlStats {
+    pub total_chapters: usize, // 目录中的章节总数
+    pub processed_chapters: usize, // 已生成XHTML的章节数
+    pub illustrated_chapters: usize, // 含插图的章节数
+    pub char_count: usize, // 正文字符数（不含空白）
+    pub word_count: usize, // 正文词数
+}
+
+impl CrawlStats {
+    /// 根据处理章节时记录的字数汇总统计（续传跳过的章节不计入字数）
+    pub fn from_epub(epub: &Epub) -> Self {
+        let mut stats = CrawlStats::default();
+        for chapter in epub.volumes.iter().flat_map(|volume| &volume.chapters) {
+            stats.total_chapters += 1;
+            if chapter.xhtml_path.is_some() {
+                stats.processed_chapters += 1;
+            }
+            if chapter.has_illustrations {
+                stats.illustrated_chapters += 1;
+            }
+            stats.char_count += chapter.char_count.unwrap_or(0);
+            stats.word_count += chapter.word_count.unwrap_or(0);
+        }
+        stats
+    }
+
+    /// 输出统计报告
+    pub fn log_report(&self) {
+        info!("=== 统计 ===");
+        info!("章节: {}/{} 已处理 ({} 章含插图)", self.processed_chapters, self.total_chapters, self.illustrated_chapters);
+        info!("字数: 约 {} 字符 / {} 词", self.char_count, self.word_count);
+    }
+}
@@ -0,0 +1,57 @@
+use reqwest::RequestBuilder;
+use reqwest::header::USER_AGENT;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 默认的User-Agent
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36";
+
+/// User-Agent轮换器
+///
+/// 只有一个UA时直接设置在client上，不做逐请求覆盖；
+/// 提供多个UA时按轮询顺序为每个请求设置User-Agent头。
+#[derive(Clone)]
+pub struct UserAgentRotator {
+    agents: Arc<Vec<String>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl Default for UserAgentRotator {
+    fn default() -> Self {
+        Self::new(vec![DEFAULT_USER_AGENT.to_string()])
+    }
+}
+
+impl UserAgentRotator {
+    pub fn new(agents: Vec<String>) -> Self {
+        let agents = if agents.is_empty() {
+            vec![DEFAULT_USER_AGENT.to_string()]
+        } else {
+            agents
+        };
+        Self {
+            agents: Arc::new(agents),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// client默认使用的User-Agent（第一个）
+    pub fn default_agent(&self) -> &str {
+        &self.agents[0]
+    }
+
+    /// 轮询取得下一个User-Agent
+    pub fn next_agent(&self) -> &str {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.agents.len();
+        &self.agents[index]
+    }
+
+    /// 为请求设置本次使用的User-Agent
+    pub fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.agents.len() > 1 {
+            request.header(USER_AGENT, self.next_agent())
+        } else {
+            request
+        }
+    }
+}
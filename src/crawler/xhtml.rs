@@ -0,0 +1,71 @@
+use scraper::{ElementRef, Html};
+use scraper::node::Node;
+use crate::utils::{collapse_whitespace, escape_xml};
+
+/// HTML中没有结束标签的空元素，在XHTML中需要自闭合
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input",
+    "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// 带前缀但在章节XHTML中已声明命名空间的属性，其他带冒号的属性名会破坏XML命名空间
+const PREFIXED_ATTRS: &[&str] = &["xml:lang", "epub:type"];
+
+/// 将HTML片段规范化为XHTML：空元素自闭合，标签成对闭合，文本和属性值按XML转义
+///
+/// 片段先经过HTML解析器补全未闭合的标签、丢弃多余的结束标签，再重新序列化，注释等非内容节点会被丢弃；
+/// 文本中来自源码排版的连续空白（换行、缩进、不间断空格）合并为单个空格。
+/// 不是合法XML名称的属性（如 `@click`）被丢弃，标签名不合法的元素只保留其内容，保证输出是格式良好的XML。
+pub fn normalize_xhtml(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut output = String::with_capacity(html.len());
+    write_children(fragment.root_element(), &mut output);
+    output
+}
+
+fn write_children(element: ElementRef, output: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => output.push_str(&escape_xml(&collapse_whitespace(text))),
+            Node::Element(_) => {
+                if let Some(child_element) = ElementRef::wrap(child) {
+                    write_element(child_element, output);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_element(element: ElementRef, output: &mut String) {
+    let name = element.value().name();
+    if !is_xml_name(name) {
+        write_children(element, output);
+        return;
+    }
+    output.push('<');
+    output.push_str(name);
+    for (attr_name, attr_value) in element.value().attrs() {
+        if is_xml_name(attr_name) || PREFIXED_ATTRS.contains(&attr_name) {
+            output.push_str(&format!(" {}=\"{}\"", attr_name, escape_xml(attr_value)));
+        }
+    }
+
+    if VOID_ELEMENTS.contains(&name) {
+        output.push_str("/>");
+        return;
+    }
+
+    output.push('>');
+    write_children(element, output);
+    output.push_str("</");
+    output.push_str(name);
+    output.push('>');
+}
+
+/// 是否为不带前缀的合法XML名称：以字母或下划线开头，其余为字母、数字、`-`、`_`、`.`
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
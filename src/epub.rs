@@ -2,32 +2,249 @@ pub mod compression;
 pub mod metadata;
 pub mod chapter;
 pub mod volume;
+pub mod stream;
+pub mod validate;
+pub mod overrides;
 
-pub use compression::EpubCompressor;
-pub use metadata::MetadataGenerator;
+pub use compression::{EpubCompressor, FilenameSuffix};
+pub use stream::StreamingEpubWriter;
+pub use metadata::{MetadataGenerator, ReadingDirection};
 pub use volume::{Volume, VolumeBuilder};
 pub use chapter::{Chapter, ChapterBuilder};
+pub use overrides::MetadataOverrides;
 
-use anyhow::Result;
-use std::path::Path;
+use crate::error::{DoclnError, Result};
+use crate::utils::{escape_xml, normalize_url};
+use std::fs;
+use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
+use log::{debug, info, warn};
 
+/// 图片在OEBPS下的存放目录，manifest中的图片href都以此开头
+pub const IMAGES_DIR: &str = "images";
+
+/// EPUB工作目录下的图片目录；下载图片和打包时扫描图片都以此为准，保证两边使用同一目录
+pub fn images_dir(epub_dir: &Path) -> PathBuf {
+    epub_dir.join("OEBPS").join(IMAGES_DIR)
+}
+
+/// 默认的书籍语言（BCP 47语言标签），docln上的小说均为越南语
+pub const DEFAULT_LANGUAGE: &str = "vi";
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+/// 小说连载状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NovelStatus {
+    Ongoing,
+    Completed,
+    Paused,
+    Other(String), // 无法识别的状态，保留原文
+}
+
+impl NovelStatus {
+    /// 将站点上的越南语状态文本归一化
+    pub fn from_label(label: &str) -> Self {
+        match label.trim().to_lowercase().as_str() {
+            "đang tiến hành" | "đang ra" => NovelStatus::Ongoing,
+            "đã hoàn thành" | "hoàn thành" => NovelStatus::Completed,
+            "tạm ngưng" | "tạm dừng" => NovelStatus::Paused,
+            _ => NovelStatus::Other(label.trim().to_string()),
+        }
+    }
+
+    /// 用于元数据的状态值
+    pub fn as_str(&self) -> &str {
+        match self {
+            NovelStatus::Ongoing => "ongoing",
+            NovelStatus::Completed => "completed",
+            NovelStatus::Paused => "paused",
+            NovelStatus::Other(label) => label,
+        }
+    }
+
+    /// 用于显示的状态名称
+    pub fn display_name(&self) -> &str {
+        match self {
+            NovelStatus::Ongoing => "连载中",
+            NovelStatus::Completed => "已完结",
+            NovelStatus::Paused => "暂停",
+            NovelStatus::Other(label) => label,
+        }
+    }
+}
+
+/// 小说信息，与 [`Volume`]、[`Chapter`] 一起是爬虫、EPUB生成和导出共用的唯一数据模型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Epub {
     pub id: u32,
     pub title: String,
+    #[serde(default)]
+    pub other_titles: Vec<String>, // 其他名称（原名、罗马音等）
     pub author: String,
     pub illustrator: Option<String>, // 插画师
-    pub summary: String, // 简介内容
+    pub status: Option<NovelStatus>, // 连载状态
+    #[serde(default)]
+    pub rating: Option<f32>, // 评分
+    #[serde(default)]
+    pub views: Option<u64>, // 浏览量
+    #[serde(default)]
+    pub likes: Option<u64>, // 点赞数
+    #[serde(default = "default_language")]
+    pub language: String, // 写入content.opf的dc:language和各页面的xml:lang
+    pub summary: Vec<String>, // 简介内容，每个段落一项
     pub cover_image_path: Option<String>, // 封面图片本地路径
     pub volumes: Vec<Volume>, // 卷信息
+    #[serde(default)]
+    pub genres: Vec<String>, // 官方分类；页面未区分分类和用户标签时为空，全部放在tags中
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub updated: Option<String>, // 小说最后更新日期（YYYY-MM-DD），写入content.opf的修改日期
     pub url: String,
 }
 
+impl Epub {
+    /// 以换行连接的完整简介
+    pub fn summary_text(&self) -> String {
+        self.summary.join("\n")
+    }
+
+    /// 按阅读顺序列出所有卷的章节URL，相对URL以base_url（如 `https://docln.net`）补全
+    pub fn chapter_urls(&self, base_url: &str) -> Vec<String> {
+        self.volume_chapter_urls(base_url).into_iter().map(|(_, url)| url).collect()
+    }
+
+    /// 与 [`Epub::chapter_urls`] 相同，但每个URL附带所在卷的索引
+    pub fn volume_chapter_urls(&self, base_url: &str) -> Vec<(usize, String)> {
+        self.volumes
+            .iter()
+            .enumerate()
+            .flat_map(|(volume_index, volume)| {
+                volume.chapters.iter().map(move |chapter| (volume_index, normalize_url(&chapter.url, base_url)))
+            })
+            .collect()
+    }
+
+    /// 生成小说封面页 text/cover.xhtml，整页显示封面图片；没有下载封面时返回false
+    pub fn generate_cover_page(&self, oebps_dir: &Path) -> Result<bool> {
+        let Some(xhtml_content) = self.cover_page_xhtml() else {
+            return Ok(false);
+        };
+
+        let text_dir = oebps_dir.join("text");
+        fs::create_dir_all(&text_dir)?;
+        let cover_page_path = text_dir.join("cover.xhtml");
+        fs::write(&cover_page_path, xhtml_content)?;
+        debug!("封面页已生成: {}", cover_page_path.display());
+        Ok(true)
+    }
+
+    /// 小说封面页的内容，没有下载封面时为None
+    pub fn cover_page_xhtml(&self) -> Option<String> {
+        let cover_path = self.cover_image_path.as_ref()?;
+
+        let mut xhtml_content = String::new();
+        xhtml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang=""#);
+        xhtml_content.push_str(&escape_xml(&self.language));
+        xhtml_content.push_str(r#"">
+<head>
+    <title>"#);
+        xhtml_content.push_str(&escape_xml(&self.title));
+        xhtml_content.push_str(r#"</title>
+    <meta http-equiv="Content-Type" content="text/html; charset=UTF-8"/>
+    <link rel="stylesheet" type="text/css" href="../styles/stylesheet.css"/>
+</head>
+<body class="cover-page" epub:type="cover">
+    <div class="cover">
+"#);
+        xhtml_content.push_str(&format!(
+            "        <img src=\"../{}\" alt=\"{}\" class=\"volume-cover-img\"/>\n",
+            cover_path, escape_xml(&self.title)
+        ));
+        xhtml_content.push_str(r#"    </div>
+</body>
+</html>"#);
+        Some(xhtml_content)
+    }
+
+    /// 生成标题页 text/title.xhtml，包含标题、作者、插画师、标签和简介
+    pub fn generate_title_page(&self, oebps_dir: &Path) -> Result<()> {
+        let text_dir = oebps_dir.join("text");
+        fs::create_dir_all(&text_dir)?;
+        let title_page_path = text_dir.join("title.xhtml");
+        fs::write(&title_page_path, self.title_page_xhtml())?;
+        debug!("标题页已生成: {}", title_page_path.display());
+        Ok(())
+    }
+
+    /// 标题页的内容
+    pub fn title_page_xhtml(&self) -> String {
+        let mut xhtml_content = String::new();
+        xhtml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xml:lang=""#);
+        xhtml_content.push_str(&escape_xml(&self.language));
+        xhtml_content.push_str(r#"">
+<head>
+    <title>"#);
+        xhtml_content.push_str(&escape_xml(&self.title));
+        xhtml_content.push_str(r#"</title>
+    <meta http-equiv="Content-Type" content="text/html; charset=UTF-8"/>
+    <link rel="stylesheet" type="text/css" href="../styles/stylesheet.css"/>
+</head>
+<body>
+    <div class="title-page">
+        <h1>"#);
+        xhtml_content.push_str(&escape_xml(&self.title));
+        xhtml_content.push_str("</h1>\n");
+        xhtml_content.push_str(&format!("        <p class=\"author\">作者: {}</p>\n", escape_xml(&self.author)));
+        if let Some(illustrator) = &self.illustrator {
+            xhtml_content.push_str(&format!("        <p class=\"illustrator\">插画师: {}</p>\n", escape_xml(illustrator)));
+        }
+        if let Some(status) = &self.status {
+            xhtml_content.push_str(&format!("        <p class=\"status\">状态: {}</p>\n", escape_xml(status.display_name())));
+        }
+        if !self.genres.is_empty() {
+            let genres = self.genres.iter().map(|genre| escape_xml(genre)).collect::<Vec<_>>().join(", ");
+            xhtml_content.push_str(&format!("        <p class=\"genres\">分类: {}</p>\n", genres));
+        }
+        if !self.tags.is_empty() {
+            let tags = self.tags.iter().map(|tag| escape_xml(tag)).collect::<Vec<_>>().join(", ");
+            xhtml_content.push_str(&format!("        <p class=\"tags\">标签: {}</p>\n", tags));
+        }
+
+        // 简介每个段落单独一个<p>
+        if !self.summary.is_empty() {
+            xhtml_content.push_str("        <div class=\"summary\">\n            <h2>简介</h2>\n");
+            for paragraph in &self.summary {
+                xhtml_content.push_str(&format!("            <p>{}</p>\n", escape_xml(paragraph)));
+            }
+            xhtml_content.push_str("        </div>\n");
+        }
+
+        xhtml_content.push_str(r#"    </div>
+</body>
+</html>"#);
+        xhtml_content
+    }
+}
+
 pub struct EpubGenerator {
     epub: Epub,
     epub_dir: Option<String>,
+    reading_direction: ReadingDirection,
+    illustration_gallery: bool,
+    keep_staging: bool,
+    language: Option<String>,
+    identifier: Option<String>,
+    per_volume: bool,
+    compression_level: Option<u32>,
+    filename_suffix: FilenameSuffix,
+    stream: Option<StreamingEpubWriter>,
 }
 
 impl EpubGenerator {
@@ -35,6 +252,15 @@ impl EpubGenerator {
         Self {
             epub,
             epub_dir: None,
+            reading_direction: ReadingDirection::default(),
+            illustration_gallery: false,
+            keep_staging: false,
+            language: None,
+            identifier: None,
+            per_volume: false,
+            compression_level: None,
+            filename_suffix: FilenameSuffix::None,
+            stream: None,
         }
     }
 
@@ -43,27 +269,240 @@ impl EpubGenerator {
         self
     }
 
-    pub fn generate(self) -> Result<String> {
-        let epub_dir = self.epub_dir.ok_or_else(|| anyhow::anyhow!("EPUB directory is required"))?;
+    /// 设置阅读方向，默认从左到右
+    pub fn reading_direction(mut self, reading_direction: ReadingDirection) -> Self {
+        self.reading_direction = reading_direction;
+        self
+    }
 
-        // 创建 EPUB 结构体
+    /// 设置是否为每卷生成插图集页面（放在卷封面之后）
+    pub fn illustration_gallery(mut self, illustration_gallery: bool) -> Self {
+        self.illustration_gallery = illustration_gallery;
+        self
+    }
 
-        let metadata_generator = MetadataGenerator::new();
-        
+    /// 设置生成EPUB后是否保留暂存文件夹，便于检查中间文件
+    pub fn keep_staging(mut self, keep_staging: bool) -> Self {
+        self.keep_staging = keep_staging;
+        self
+    }
+
+    /// 设置书籍语言（如 vi、en），覆盖小说信息中的语言
+    pub fn language<S: Into<String>>(mut self, language: S) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// 设置书籍唯一标识（写入dc:identifier和toc.ncx的dtb:uid），默认为 urn:docln:{id}
+    ///
+    /// 分卷模式下各卷的标识为 {identifier}:volN。
+    pub fn identifier<S: Into<String>>(mut self, identifier: S) -> Self {
+        self.identifier = Some(identifier.into());
+        self
+    }
+
+    /// 设置分卷模式：每卷生成一个独立的EPUB（docln_{id}_volXXX.epub），默认合并为一本
+    pub fn per_volume(mut self, per_volume: bool) -> Self {
+        self.per_volume = per_volume;
+        self
+    }
+
+    /// 设置文本和XML的Deflate压缩级别（0-9），数值越小打包越快；插图总是直接存储不再压缩
+    pub fn compression_level(mut self, compression_level: u32) -> Self {
+        self.compression_level = Some(compression_level);
+        self
+    }
+
+    /// 设置EPUB文件名后缀（打包日期或内容哈希），便于保留多次爬取的版本；默认不加，重新生成会覆盖旧文件
+    pub fn filename_suffix(mut self, filename_suffix: FilenameSuffix) -> Self {
+        self.filename_suffix = filename_suffix;
+        self
+    }
+
+    /// 设置流式写入器：章节和图片已在爬取时写入其中，生成时只追加元数据和辅助页面，不使用暂存文件夹
+    ///
+    /// 写入器应以 docln_{id}.epub.part 创建，完成后按文件名后缀重命名；流式模式不支持分卷。
+    pub fn stream(mut self, stream: StreamingEpubWriter) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// 仅对已存在的工作目录重新执行压缩，用于压缩失败后的恢复；工作目录会被保留
+    pub fn compress_only<P: AsRef<Path>>(work_dir: P) -> Result<String> {
+        let work_dir = work_dir.as_ref();
+        if !work_dir.join("mimetype").exists() || !work_dir.join("OEBPS").join("content.opf").exists() {
+            return Err(DoclnError::Epub(format!("{} 不是有效的EPUB工作目录", work_dir.display())));
+        }
+
+        // 工作目录是恢复的唯一来源，压缩后不删除
+        let compressor = EpubCompressor::new().keep_staging(true);
+        compressor.compress_epub(work_dir)
+    }
+
+    /// 生成EPUB文件并返回文件名；分卷模式下返回第一卷的文件名，全部文件名见 [`generate_files`](Self::generate_files)
+    pub fn generate(self) -> Result<String> {
+        self.generate_files()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| DoclnError::Epub("没有包含已下载章节的卷，未生成EPUB".to_string()))
+    }
+
+    /// 生成EPUB文件，返回全部文件名：合并模式下只有一个，分卷模式下每个有已下载章节的卷一个
+    pub fn generate_files(mut self) -> Result<Vec<String>> {
+        let epub_dir = self.epub_dir.take().ok_or_else(|| DoclnError::Epub("EPUB directory is required".to_string()))?;
+        if let Some(language) = self.language.take() {
+            self.epub.language = language;
+        }
         let epub_path = Path::new(&epub_dir);
-        
-        // 生成所有元数据文件
-        metadata_generator.generate_all_metadata(&self.epub, epub_path, self.epub.id)?;
-        
-        // 生成卷封面章节
+        let identifier = self.identifier.take().unwrap_or_else(|| metadata::default_identifier(self.epub.id));
+
+        if let Some(stream) = self.stream.take() {
+            if self.per_volume {
+                return Err(DoclnError::InvalidInput("流式写入不支持分卷模式".to_string()));
+            }
+            return Ok(vec![self.package_stream(&stream, &identifier)?]);
+        }
+
+        if !self.per_volume {
+            return Ok(vec![self.package(&self.epub, epub_path, &identifier)?]);
+        }
+
+        let mut generated = Vec::new();
+        for (volume_index, volume) in self.epub.volumes.iter().enumerate() {
+            if volume.chapters.iter().all(|chapter| chapter.xhtml_path.is_none()) {
+                continue;
+            }
+            let volume_epub = volume_only(&self.epub, volume_index);
+            let volume_dir = stage_volume(&volume_epub, volume_index, epub_path)?;
+            let volume_identifier = format!("{}:vol{}", identifier, volume_index + 1);
+            generated.push(self.package(&volume_epub, &volume_dir, &volume_identifier)?);
+        }
+        if generated.is_empty() {
+            warn!("没有包含已下载章节的卷，未生成EPUB");
+        }
+
+        // 各卷所需的文件已复制到各自的暂存文件夹，共用的暂存文件夹按keep_staging处理
+        if !self.keep_staging {
+            debug!("正在清理临时文件夹: {}", epub_path.display());
+            if let Err(e) = fs::remove_dir_all(epub_path) {
+                warn!("清理失败: {}", e);
+            }
+        }
+        Ok(generated)
+    }
+
+    /// 元数据文件和辅助页面（封面页、标题页、卷首页、插图集）的 (相对工作目录的路径, 内容)
+    ///
+    /// 暂存文件夹和流式写入两种方式都由此生成，保证两者打包出的EPUB结构相同。
+    fn package_files(&self, epub: &Epub, identifier: &str) -> Vec<(String, String)> {
+        let metadata_generator = MetadataGenerator::new()
+            .reading_direction(self.reading_direction)
+            .illustration_gallery(self.illustration_gallery)
+            .identifier(identifier);
+
+        let mut files = metadata_generator.metadata_files(epub, epub.id);
+        if let Some(cover_page) = epub.cover_page_xhtml() {
+            files.push(("OEBPS/text/cover.xhtml".to_string(), cover_page));
+        }
+        files.push(("OEBPS/text/title.xhtml".to_string(), epub.title_page_xhtml()));
+        for (i, volume) in epub.volumes.iter().enumerate() {
+            let volume_dir = format!("OEBPS/text/volume_{:03}", i + 1);
+            if let Some(opening_page) = volume.opening_page_xhtml(&epub.language) {
+                files.push((format!("{}/chapter_000.xhtml", volume_dir), opening_page));
+            }
+            if self.illustration_gallery && let Some(gallery) = volume.gallery_xhtml(&epub.language) {
+                files.push((format!("{}/gallery.xhtml", volume_dir), gallery));
+            }
+        }
+        files
+    }
+
+    /// 在暂存文件夹中生成元数据和辅助页面，校验后压缩为EPUB
+    fn package(&self, epub: &Epub, epub_path: &Path, identifier: &str) -> Result<String> {
+        // 生成所有元数据文件、封面页、标题页、卷首页和卷插图集
+        fs::create_dir_all(epub_path)?;
+        fs::write(epub_path.join("mimetype"), "application/epub+zip")?;
+        for (path, content) in self.package_files(epub, identifier) {
+            stream::write_work_file(None, &epub_path.join(path), content.as_bytes())?;
+        }
+        debug!("EPUB元数据文件已生成");
         let oebps_dir = epub_path.join("OEBPS");
-        crate::epub::chapter::generate_all_volume_cover_chapters(&self.epub, &oebps_dir)?;
+        
+        // 打包前校验manifest、spine与实际文件是否一致
+        validate::validate(&oebps_dir)?;
         
         // 压缩成EPUB文件
-        let compressor = EpubCompressor::new();
+        let compressor = EpubCompressor::new().keep_staging(self.keep_staging).filename_suffix(self.filename_suffix);
+        let compressor = match self.compression_level {
+            Some(compression_level) => compressor.compression_level(compression_level),
+            None => compressor,
+        };
         let epub_filename = compressor.compress_epub(epub_path)?;
         
-        println!("EPUB文件生成成功: {}", epub_filename);
+        info!("EPUB文件生成成功: {}", epub_filename);
+        Ok(epub_filename)
+    }
+
+    /// 向流式写入器追加元数据和辅助页面，校验后完成写入
+    fn package_stream(&self, stream: &StreamingEpubWriter, identifier: &str) -> Result<String> {
+        let files = self.package_files(&self.epub, identifier);
+        let content_opf = files.iter().find(|(path, _)| path == "OEBPS/content.opf").map(|(_, content)| content.clone()).unwrap_or_default();
+        for (path, content) in files {
+            stream.add_file(&path, content.as_bytes())?;
+        }
+
+        // 打包前校验manifest、spine与已写入的条目是否一致
+        validate::validate_package(&content_opf, |href| stream.contains(&format!("OEBPS/{}", href)))?;
+
+        let epub_filename = EpubCompressor::new().filename_suffix(self.filename_suffix).finish_stream(stream)?;
+        info!("EPUB文件生成成功: {}", epub_filename);
         Ok(epub_filename)
     }
-}
\ No newline at end of file
+}
+
+/// 只保留指定卷的小说信息，用于分卷生成
+///
+/// 其他卷保留在原位置但清空章节、封面和插画师，使卷的编号（文件路径和manifest id）与合并模式一致。
+fn volume_only(epub: &Epub, volume_index: usize) -> Epub {
+    let mut volume_epub = epub.clone();
+    for (i, volume) in volume_epub.volumes.iter_mut().enumerate() {
+        if i == volume_index {
+            volume_epub.title = format!("{} - {}", epub.title, volume.title);
+            volume_epub.illustrator = volume.illustrator_or(epub.illustrator.as_deref()).map(str::to_string);
+        } else {
+            volume.chapters.clear();
+            volume.cover_image_path = None;
+            volume.illustrator = None;
+        }
+    }
+    volume_epub
+}
+
+/// 将单卷所需的文件（小说封面、卷封面、插图和章节）复制到 {工作目录}_volXXX 暂存文件夹
+fn stage_volume(volume_epub: &Epub, volume_index: usize, epub_path: &Path) -> Result<PathBuf> {
+    let dir_name = epub_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("epub_{}", volume_epub.id));
+    let volume_dir = epub_path.with_file_name(format!("{}_vol{:03}", dir_name, volume_index + 1));
+    if volume_dir.exists() {
+        fs::remove_dir_all(&volume_dir)?;
+    }
+    let source_oebps = epub_path.join("OEBPS");
+    let target_oebps = volume_dir.join("OEBPS");
+
+    let volume = &volume_epub.volumes[volume_index];
+    let mut files: Vec<String> = volume_epub.cover_image_path.iter().chain(&volume.cover_image_path).cloned().collect();
+    files.extend(volume.illustration_paths());
+    files.extend(volume.chapters.iter().filter_map(|chapter| chapter.xhtml_path.clone()));
+
+    for file in files {
+        let target = target_oebps.join(&file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source_oebps.join(&file), &target)?;
+    }
+    debug!("卷 '{}' 的暂存文件夹已生成: {}", volume.title, volume_dir.display());
+    Ok(volume_dir)
+}
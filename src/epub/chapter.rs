@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::error::Result;
 use std::path::Path;
 use serde::{Serialize, Deserialize};
 use super::Epub;
@@ -8,7 +8,14 @@ pub struct Chapter {
     pub title: String,
     pub url: String,
     pub has_illustrations: bool, // 是否包含插图
+    #[serde(default)]
+    pub illustration_paths: Vec<String>, // 已下载的插图（相对OEBPS目录的路径，按文中顺序）
+    #[serde(default)]
+    pub has_splash: bool, // 第一张插图是章节开头的整页插图（正文之前），而非文中插图
     pub xhtml_path: Option<String>, // XHTML文件路径（用于EPUB）
+    pub published: Option<String>, // 章节列表中显示的发布/更新时间
+    pub char_count: Option<usize>, // 正文字符数（处理章节时统计）
+    pub word_count: Option<usize>, // 正文词数（处理章节时统计）
 }
 
 impl Chapter {
@@ -22,6 +29,13 @@ pub struct ChapterBuilder {
     url: String,
     has_illustrations: bool,
     xhtml_path: Option<String>,
+    published: Option<String>,
+}
+
+impl Default for ChapterBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ChapterBuilder {
@@ -31,6 +45,7 @@ impl ChapterBuilder {
             url: String::new(),
             has_illustrations: false,
             xhtml_path: None,
+            published: None,
         }
     }
 
@@ -54,20 +69,33 @@ impl ChapterBuilder {
         self
     }
 
+    pub fn published(mut self, published: Option<String>) -> Self {
+        self.published = published;
+        self
+    }
+
     pub fn build(self) -> Chapter {
         Chapter {
             title: self.title,
             url: self.url,
             has_illustrations: self.has_illustrations,
+            illustration_paths: Vec::new(),
+            has_splash: false,
             xhtml_path: self.xhtml_path,
+            published: self.published,
+            char_count: None,
+            word_count: None,
         }
     }
 }
 
+/// 为每卷生成卷首页：有卷封面的卷为封面页，其余有已下载章节的卷为卷标题分隔页
 pub fn generate_all_volume_cover_chapters(epub: &Epub, oebps_dir: &Path) -> Result<()> {
     for (i, volume) in epub.volumes.iter().enumerate() {
         if volume.cover_image_path.is_some() {
-            volume.generate_volume_cover_chapter(i, oebps_dir)?;
+            volume.generate_volume_cover_chapter(i, oebps_dir, &epub.language)?;
+        } else if volume.has_opening_page() {
+            volume.generate_volume_title_chapter(i, oebps_dir, &epub.language)?;
         }
     }
     Ok(())
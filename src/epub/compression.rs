@@ -1,66 +1,139 @@
-use anyhow::Result;
+use crate::error::Result;
 use std::fs;
-use std::fs::File;
-use std::io::Write;
 use std::path::Path;
-use zip::write::FileOptions;
-use zip::ZipWriter;
+use super::StreamingEpubWriter;
+use crate::utils::sha256_hex;
+use log::{debug, info, warn};
 
-pub struct EpubCompressor;
+/// EPUB文件名后缀，用于保留同一小说多次爬取的版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilenameSuffix {
+    /// 不加后缀：docln_{id}.epub（默认），重新爬取会覆盖之前的文件
+    #[default]
+    None,
+    /// 打包日期：docln_{id}_{yyyymmdd}.epub
+    Date,
+    /// 文件内容SHA-256的前8位：docln_{id}_{sha8}.epub，内容相同时文件名也相同
+    ContentHash,
+}
+
+pub struct EpubCompressor {
+    keep_staging: bool,
+    compression_level: Option<i64>,
+    filename_suffix: FilenameSuffix,
+}
+
+impl Default for EpubCompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl EpubCompressor {
     pub fn new() -> Self {
-        Self
+        Self { keep_staging: false, compression_level: None, filename_suffix: FilenameSuffix::None }
+    }
+
+    /// 设置EPUB文件名后缀，默认不加
+    pub fn filename_suffix(mut self, filename_suffix: FilenameSuffix) -> Self {
+        self.filename_suffix = filename_suffix;
+        self
+    }
+
+    /// 设置文本和XML条目的Deflate压缩级别（0-9，超出时按9处理），图片总是直接存储
+    pub fn compression_level(mut self, compression_level: u32) -> Self {
+        self.compression_level = Some(i64::from(compression_level.min(9)));
+        self
+    }
+
+    /// 设置压缩完成后是否保留EPUB暂存文件夹
+    pub fn keep_staging(mut self, keep_staging: bool) -> Self {
+        self.keep_staging = keep_staging;
+        self
+    }
+
+    /// 完成流式写入器，并按文件名后缀把写入中的 docln_{id}.epub.part 重命名为最终文件名，返回文件名
+    pub fn finish_stream(&self, writer: &StreamingEpubWriter) -> Result<String> {
+        let part_path = writer.finish()?;
+        let file_name = part_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let file_stem = file_name.strip_suffix(".epub.part").or_else(|| file_name.strip_suffix(".epub")).unwrap_or(&file_name);
+        let epub_filename = match self.filename_suffix {
+            FilenameSuffix::None => format!("{}.epub", file_stem),
+            FilenameSuffix::Date => format!("{}_{}.epub", file_stem, chrono::Local::now().format("%Y%m%d")),
+            FilenameSuffix::ContentHash => {
+                let content_hash = sha256_hex(&fs::read(&part_path)?);
+                format!("{}_{}.epub", file_stem, &content_hash[..8])
+            }
+        };
+        let epub_path = part_path.with_file_name(&epub_filename);
+        if epub_path != part_path {
+            fs::rename(&part_path, &epub_path)?;
+        }
+
+        info!("EPUB文件已生成: {}", epub_path.display());
+        Ok(epub_filename)
     }
 
     /// 压缩EPUB文件夹为EPUB文件
     pub fn compress_epub(&self, epub_dir: &Path) -> Result<String> {
         // 从目录名提取ID，目录名格式为 epub_{id}，转换为 docln_{id}
         let dir_name = epub_dir.file_name().unwrap().to_string_lossy();
-        let epub_filename = if dir_name.starts_with("epub_") {
-            format!("docln_{}.epub", &dir_name[5..])
+        let file_stem = if let Some(id) = dir_name.strip_prefix("epub_") {
+            format!("docln_{}", id)
         } else {
-            format!("docln_{}.epub", &dir_name)
+            format!("docln_{}", &dir_name)
         };
-        let epub_path = epub_dir.parent().unwrap().join(&epub_filename);
-        
-        println!("正在压缩EPUB文件: {}", epub_filename);
+        let mut epub_filename = match self.filename_suffix {
+            FilenameSuffix::Date => format!("{}_{}.epub", file_stem, chrono::Local::now().format("%Y%m%d")),
+            // 哈希要等文件写完才能计算，先写入临时文件名
+            FilenameSuffix::None | FilenameSuffix::ContentHash => format!("{}.epub", file_stem),
+        };
+        let mut epub_path = epub_dir.parent().unwrap().join(&epub_filename);
+        if self.filename_suffix == FilenameSuffix::ContentHash {
+            epub_path.set_extension("epub.part");
+        }
         
-        // 创建ZIP文件
-        let file = File::create(&epub_path)?;
-        let mut zip = ZipWriter::new(file);
+        info!("正在压缩EPUB文件: {}", epub_filename);
         
-        // EPUB标准要求mimetype文件必须第一个添加且不压缩
-        let mimetype_path = epub_dir.join("mimetype");
-        if mimetype_path.exists() {
-            let options: FileOptions<'_, ()> = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
-            zip.start_file("mimetype", options)?;
-            let mimetype_content = fs::read(&mimetype_path)?;
-            zip.write_all(&mimetype_content)?;
-        }
+        // 创建ZIP文件，mimetype由写入器第一个写入且不压缩
+        let writer = StreamingEpubWriter::new(&epub_path)?.compression_level(self.compression_level);
         
         // 递归添加目录中的所有文件
-        self.add_directory_to_zip(&mut zip, epub_dir, "")?;
+        self.add_directory_to_zip(&writer, epub_dir, "")?;
         
         // 完成ZIP文件
-        zip.finish()?;
+        writer.finish()?;
+        
+        if self.filename_suffix == FilenameSuffix::ContentHash {
+            let content_hash = sha256_hex(&fs::read(&epub_path)?);
+            epub_filename = format!("{}_{}.epub", file_stem, &content_hash[..8]);
+            let hashed_path = epub_path.with_file_name(&epub_filename);
+            fs::rename(&epub_path, &hashed_path)?;
+            epub_path = hashed_path;
+        }
+        
+        info!("EPUB文件已生成: {}", epub_path.display());
         
-        println!("EPUB文件已生成: {}", epub_path.display());
+        if self.keep_staging {
+            info!("保留暂存文件夹: {}", epub_dir.display());
+            return Ok(epub_filename);
+        }
         
-        // 删除EPUB文件夹
-        println!("正在清理临时文件夹: {}", epub_dir.display());
+        // 删除EPUB文件夹（只有压缩成功才会执行到这里）
+        debug!("正在清理临时文件夹: {}", epub_dir.display());
         match fs::remove_dir_all(epub_dir) {
-            Ok(()) => println!("清理成功"),
-            Err(e) => println!("清理失败: {}", e),
+            Ok(()) => debug!("清理成功"),
+            Err(e) => warn!("清理失败: {}", e),
         }
         
         Ok(epub_filename)
     }
     
-    /// 递归添加目录到ZIP文件
-    fn add_directory_to_zip(&self, zip: &mut ZipWriter<File>, dir: &Path, base_path: &str) -> Result<()> {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
+    /// 递归添加目录到ZIP文件，条目按文件名排序，使输出与文件系统的遍历顺序无关
+    fn add_directory_to_zip(&self, writer: &StreamingEpubWriter, dir: &Path, base_path: &str) -> Result<()> {
+        let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
             let path = entry.path();
             let file_name = entry.file_name();
             let file_name_str = file_name.to_string_lossy();
@@ -77,7 +150,7 @@ impl EpubCompressor {
                 } else {
                     format!("{}/{}", base_path, file_name_str)
                 };
-                self.add_directory_to_zip(zip, &path, &new_base_path)?;
+                self.add_directory_to_zip(writer, &path, &new_base_path)?;
             } else {
                 // 添加文件到ZIP
                 let zip_path = if base_path.is_empty() {
@@ -86,11 +159,10 @@ impl EpubCompressor {
                     format!("{}/{}", base_path, file_name_str)
                 };
                 
-                zip.start_file(&zip_path, FileOptions::<'_, ()>::default())?;
                 let file_content = fs::read(&path)?;
-                zip.write_all(&file_content)?;
+                writer.add_file(&zip_path, &file_content)?;
                 
-                println!("已添加文件: {}", zip_path);
+                debug!("已添加文件: {}", zip_path);
             }
         }
         Ok(())
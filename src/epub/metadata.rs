@@ -1,13 +1,145 @@
-use anyhow::Result;
+use crate::error::Result;
 use std::fs;
 use std::path::Path;
+use serde::{Serialize, Deserialize};
 use super::Epub;
+use crate::utils::{escape_xml, parse_date};
+use chrono::NaiveDate;
+use log::debug;
 
-pub struct MetadataGenerator;
+/// 阅读方向，写入content.opf的 `<meta name="primary-writing-mode">`
+///
+/// spine的page-progression-direction属性是EPUB3才有的，OPF 2.0中改用这个meta显式声明书写方向。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReadingDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl ReadingDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadingDirection::Ltr => "ltr",
+            ReadingDirection::Rtl => "rtl",
+        }
+    }
+
+    /// primary-writing-mode的取值
+    pub fn writing_mode(&self) -> &'static str {
+        match self {
+            ReadingDirection::Ltr => "horizontal-lr",
+            ReadingDirection::Rtl => "horizontal-rl",
+        }
+    }
+}
+
+/// META-INF/container.xml的内容
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>"#;
+
+/// 内嵌样式表的内容
+const STYLESHEET: &str = r#"body.cover-page {
+    margin: 0;
+    padding: 0;
+}
+
+div.cover {
+    height: 100vh;
+    margin: 0;
+    padding: 0;
+    text-align: center;
+    page-break-before: always;
+    page-break-after: always;
+}
+
+h1.volume-cover-title {
+    font-size: 1.2em;
+    margin: 0.5em 0;
+}
+
+img.volume-cover-img {
+    height: 90%;
+    max-width: 100%;
+    object-fit: contain;
+}
+
+div.title-page {
+    text-align: center;
+}
+
+p.volume-illustrator {
+    text-align: center;
+}
+
+img.chapter-splash {
+    display: block;
+    width: 100%;
+    height: auto;
+    margin: 0 auto;
+    page-break-after: always;
+}
+
+div.volume-title {
+    margin-top: 30%;
+    text-align: center;
+    page-break-before: always;
+    page-break-after: always;
+}
+
+div.title-page div.summary {
+    text-align: left;
+}
+"#;
+
+pub struct MetadataGenerator {
+    reading_direction: ReadingDirection,
+    illustration_gallery: bool,
+    identifier: Option<String>,
+}
+
+impl Default for MetadataGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl MetadataGenerator {
     pub fn new() -> Self {
-        Self
+        Self {
+            reading_direction: ReadingDirection::default(),
+            illustration_gallery: false,
+            identifier: None,
+        }
+    }
+
+    pub fn illustration_gallery(mut self, illustration_gallery: bool) -> Self {
+        self.illustration_gallery = illustration_gallery;
+        self
+    }
+
+    /// 该卷是否生成插图集页面
+    fn has_gallery(&self, volume: &crate::epub::Volume) -> bool {
+        self.illustration_gallery && volume.chapters.iter().any(|chapter| !chapter.illustration_paths.is_empty())
+    }
+
+    pub fn reading_direction(mut self, reading_direction: ReadingDirection) -> Self {
+        self.reading_direction = reading_direction;
+        self
+    }
+
+    /// 设置书籍唯一标识（dc:identifier和dtb:uid），默认为 urn:docln:{novel_id}
+    pub fn identifier<S: Into<String>>(mut self, identifier: S) -> Self {
+        self.identifier = Some(identifier.into());
+        self
+    }
+
+    fn book_identifier(&self, novel_id: u32) -> String {
+        self.identifier.clone().unwrap_or_else(|| default_identifier(novel_id))
     }
 
     /// 生成mimetype文件
@@ -19,136 +151,218 @@ impl MetadataGenerator {
 
     /// 生成container.xml文件
     pub fn generate_container_xml(&self, meta_inf_dir: &Path) -> Result<()> {
-        let container_content = r#"<?xml version="1.0" encoding="UTF-8"?>
-<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
-    <rootfiles>
-        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
-    </rootfiles>
-</container>"#;
-        fs::write(meta_inf_dir.join("container.xml"), container_content)?;
+        fs::write(meta_inf_dir.join("container.xml"), CONTAINER_XML)?;
+        Ok(())
+    }
+
+    /// 生成样式表文件
+    pub fn generate_stylesheet(&self, oebps_dir: &Path) -> Result<()> {
+        let styles_dir = oebps_dir.join("styles");
+        fs::create_dir_all(&styles_dir)?;
+        fs::write(styles_dir.join("stylesheet.css"), STYLESHEET)?;
         Ok(())
     }
 
     /// 生成content.opf文件
     pub fn generate_content_opf(&self, epub: &Epub, oebps_dir: &Path, novel_id: u32) -> Result<()> {
+        fs::write(oebps_dir.join("content.opf"), self.content_opf(epub, novel_id))?;
+        Ok(())
+    }
+
+    /// content.opf的内容
+    pub fn content_opf(&self, epub: &Epub, novel_id: u32) -> String {
         let mut content_opf = String::new();
         
         // OPF头部
         content_opf.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
 <package version="2.0" xmlns="http://www.idpf.org/2007/opf" unique-identifier="BookId">
     <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
-        <dc:identifier id="BookId">docln:"#);
-        content_opf.push_str(&format!("{}", novel_id));
+        <dc:identifier id="BookId">"#);
+        content_opf.push_str(&escape_xml(&self.book_identifier(novel_id)));
         content_opf.push_str(r#"</dc:identifier>
         <dc:title>"#);
-        content_opf.push_str(&epub.title);
-        content_opf.push_str(r#"</dc:title>
-        <dc:language>vi</dc:language>
+        content_opf.push_str(&escape_xml(&epub.title));
+        content_opf.push_str(r#"</dc:title>"#);
+        
+        // 其他名称作为附加标题，第一个dc:title仍为主标题；OPF 2.0没有refines，不需要给它们加id
+        for other_title in &epub.other_titles {
+            content_opf.push_str(&format!(r#"
+        <dc:title>{}</dc:title>"#, escape_xml(other_title)));
+        }
+        
+        content_opf.push_str(&format!(r#"
+        <dc:language>{}</dc:language>"#, escape_xml(&epub.language)));
+        content_opf.push_str(r#"
         <dc:creator opf:role="aut">"#);
-        content_opf.push_str(&epub.author);
+        content_opf.push_str(&escape_xml(&epub.author));
         content_opf.push_str(r#"</dc:creator>"#);
         
         // 添加插画师信息
         if let Some(illustrator) = &epub.illustrator {
             content_opf.push_str(r#"
         <dc:contributor opf:role="ill">"#);
-            content_opf.push_str(illustrator);
+            content_opf.push_str(&escape_xml(illustrator));
             content_opf.push_str(r#"</dc:contributor>"#);
         }
         
-        // 添加标签
-        for tag in &epub.tags {
+        // 各卷单独标注、且与小说插画师不同的插画师也作为插画师列出，每人只列一次
+        let mut volume_illustrators: Vec<&str> = Vec::new();
+        for volume in &epub.volumes {
+            if let Some(illustrator) = volume.illustrator.as_deref()
+                && epub.illustrator.as_deref() != Some(illustrator)
+                && !volume_illustrators.contains(&illustrator)
+            {
+                volume_illustrators.push(illustrator);
+                content_opf.push_str(&format!(r#"
+        <dc:contributor opf:role="ill">{}</dc:contributor>"#, escape_xml(illustrator)));
+            }
+        }
+        
+        // 官方分类作为dc:subject；没有分类时退回到标签，与之前的行为一致
+        let subjects = if epub.genres.is_empty() { &epub.tags } else { &epub.genres };
+        for subject in subjects {
             content_opf.push_str(r#"
         <dc:subject>"#);
-            content_opf.push_str(tag);
+            content_opf.push_str(&escape_xml(subject));
             content_opf.push_str(r#"</dc:subject>"#);
         }
         
-        // 添加简介
+        // 添加简介，段落以空格连接，避免描述中出现原始换行
         if !epub.summary.is_empty() {
             content_opf.push_str(r#"
         <dc:description>"#);
-            content_opf.push_str(&epub.summary);
+            content_opf.push_str(&escape_xml(&epub.summary.join(" ")));
             content_opf.push_str(r#"</dc:description>"#);
         }
         
         content_opf.push_str(r#"
-        <dc:publisher>docln-fetch</dc:publisher>
-        <dc:date>"#);
-        content_opf.push_str(&chrono::Local::now().format("%Y-%m-%d").to_string());
-        content_opf.push_str(r#"</dc:date>
-        <meta name="generator" content="docln-fetch"/>
+        <dc:publisher>docln-fetch</dc:publisher>"#);
+        
+        // 出版日期取最早章节的发布日期，修改日期取小说的最后更新日期（缺失时依次退回到最新章节日期和今天）
+        let chapter_dates: Vec<NaiveDate> = epub
+            .volumes
+            .iter()
+            .flat_map(|volume| &volume.chapters)
+            .filter_map(|chapter| chapter.published.as_deref().and_then(parse_date))
+            .collect();
+        if let Some(published) = chapter_dates.iter().min() {
+            content_opf.push_str(&format!(r#"
+        <dc:date opf:event="publication">{}</dc:date>"#, published.format("%Y-%m-%d")));
+        }
+        let modified = epub
+            .updated
+            .as_deref()
+            .and_then(parse_date)
+            .or_else(|| chapter_dates.iter().max().copied())
+            .unwrap_or_else(|| chrono::Local::now().date_naive());
+        content_opf.push_str(&format!(r#"
+        <dc:date opf:event="modification">{}</dc:date>
+        <meta name="generator" content="docln-fetch"/>"#, modified.format("%Y-%m-%d")));
+        
+        // 连载状态
+        if let Some(status) = &epub.status {
+            content_opf.push_str(&format!(r#"
+        <meta name="docln:status" content="{}"/>"#, escape_xml(status.as_str())));
+        }
+        
+        // 评分和统计数据
+        if let Some(rating) = epub.rating {
+            content_opf.push_str(&format!(r#"
+        <meta name="docln:rating" content="{}"/>"#, rating));
+        }
+        if let Some(views) = epub.views {
+            content_opf.push_str(&format!(r#"
+        <meta name="docln:views" content="{}"/>"#, views));
+        }
+        if let Some(likes) = epub.likes {
+            content_opf.push_str(&format!(r#"
+        <meta name="docln:likes" content="{}"/>"#, likes));
+        }
+
+        // 与分类区分开的用户标签
+        if !epub.genres.is_empty() {
+            for tag in &epub.tags {
+                content_opf.push_str(&format!(r#"
+        <meta name="docln:tag" content="{}"/>"#, escape_xml(tag)));
+            }
+        }
+        
+        // 显式声明书写方向，避免阅读器自行猜测
+        content_opf.push_str(&format!(r#"
+        <meta name="primary-writing-mode" content="{}"/>"#, self.reading_direction.writing_mode()));
+        
+        // OPF 2.0通过meta识别封面图片（manifest的properties="cover-image"是EPUB3才有的写法）
+        if epub.cover_image_path.is_some() {
+            content_opf.push_str(r#"
+        <meta name="cover" content="cover-image"/>"#);
+        }
+        
+        content_opf.push_str(r#"
     </metadata>
     <manifest>"#);
         
         // manifest内容
         content_opf.push_str(r#"
         <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
-        <item id="cover-image" href="images/cover.jpg" media-type="image/jpeg"/>"#);
+        <item id="stylesheet" href="styles/stylesheet.css" media-type="text/css"/>
+        <item id="title-page" href="text/title.xhtml" media-type="application/xhtml+xml"/>"#);
+        
+        // 添加封面图片和封面页（未下载封面时省略）
+        if let Some(cover_path) = &epub.cover_image_path {
+            content_opf.push_str(&format!(r#"
+        <item id="cover-image" href="{}" media-type="{}"/>
+        <item id="cover" href="text/cover.xhtml" media-type="application/xhtml+xml"/>"#, cover_path, media_type_for(cover_path)));
+        }
         
         // 添加卷封面图片
         for (i, volume) in epub.volumes.iter().enumerate() {
-            if let Some(cover_path) = &volume.cover_image_path {
-                if let Some(filename) = Path::new(cover_path).file_name() {
-                    if let Some(filename_str) = filename.to_str() {
-                        let media_type = if filename_str.ends_with(".png") { "image/png" } else { "image/jpeg" };
-                        content_opf.push_str(&format!(r#"
-        <item id="volume{}-cover" href="images/{}" media-type="{}"/>"#, i + 1, filename_str, media_type));
-                    }
-                }
+            if let Some(cover_path) = &volume.cover_image_path
+                && let Some(filename) = Path::new(cover_path).file_name()
+                && let Some(filename_str) = filename.to_str()
+            {
+                content_opf.push_str(&format!(r#"
+        <item id="volume{}-cover" href="images/{}" media-type="{}"/>"#, i + 1, filename_str, media_type_for(filename_str)));
             }
         }
         
         // 添加章节插图图片
         for (i, volume) in epub.volumes.iter().enumerate() {
             for (j, chapter) in volume.chapters.iter().enumerate() {
-                if chapter.has_illustrations {
-                    // 为每个有插图的章节添加图片文件声明
-                    let volume_img_dir = oebps_dir.join("images").join(format!("volume_{:03}", i + 1));
-                    let chapter_img_dir = volume_img_dir.join(format!("chapter_{:03}", j + 1));
-                    
-                    if chapter_img_dir.exists() {
-                        if let Ok(entries) = std::fs::read_dir(&chapter_img_dir) {
-                            for entry in entries.flatten() {
-                                if let Ok(file_type) = entry.file_type() {
-                                    if file_type.is_file() {
-                                        if let Some(file_name) = entry.file_name().to_str() {
-                                            if file_name.ends_with(".jpeg") || file_name.ends_with(".jpg") || file_name.ends_with(".png") {
-                                                let media_type = if file_name.ends_with(".png") { "image/png" } else { "image/jpeg" };
-                                                let img_path = format!("images/volume_{:03}/chapter_{:03}/{}", i + 1, j + 1, file_name);
-                                                let img_id = format!("vol{}_chap{}_img{}", i + 1, j + 1, file_name);
-                                                content_opf.push_str(&format!(r#"
-        <item id="{}" href="{}" media-type="{}"/>"#, img_id, img_path, media_type));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+                // 直接使用处理章节时记录的插图路径，不再扫描目录
+                // id按章节内的顺序编号，不含文件名中的点号，保证是合法且唯一的NCName
+                for (k, img_path) in chapter.illustration_paths.iter().enumerate() {
+                    let img_id = format!("img_v{:03}_c{:03}_{:03}", i + 1, j + 1, k + 1);
+                    content_opf.push_str(&format!(r#"
+        <item id="{}" href="{}" media-type="{}"/>"#, img_id, escape_xml(img_path), media_type_for(img_path)));
                 }
             }
         }
         
         // 添加章节文件
         for (i, volume) in epub.volumes.iter().enumerate() {
-            // 为有卷封面的卷添加章节0
-            if volume.cover_image_path.is_some() {
+            // 卷首页（卷封面或卷标题页）作为章节0
+            if volume.has_opening_page() {
                 let chapter0_path = format!("text/volume_{:03}/chapter_000.xhtml", i + 1);
                 content_opf.push_str(&format!(r#"
         <item id="chapter{}_0" href="{}" media-type="application/xhtml+xml"/>"#, 
                                     i + 1, chapter0_path));
             }
             
+            // 插图集页面
+            if self.has_gallery(volume) {
+                content_opf.push_str(&format!(r#"
+        <item id="gallery{}" href="text/volume_{:03}/gallery.xhtml" media-type="application/xhtml+xml"/>"#,
+                                    i + 1, i + 1));
+            }
+            
             for (j, chapter) in volume.chapters.iter().enumerate() {
-                if let Some(xhtml_path) = &chapter.xhtml_path {
-                    if let Some(filename) = Path::new(xhtml_path).file_name() {
-                        if let Some(_filename_str) = filename.to_str() {
-                            content_opf.push_str(&format!(r#"
+                if let Some(xhtml_path) = &chapter.xhtml_path
+                    && let Some(filename) = Path::new(xhtml_path).file_name()
+                    && let Some(_filename_str) = filename.to_str()
+                {
+                    content_opf.push_str(&format!(r#"
         <item id="chapter{}_{}" href="{}" media-type="application/xhtml+xml"/>"#, 
-                                i + 1, j + 1, xhtml_path));
-                        }
-                    }
+                        i + 1, j + 1, xhtml_path));
                 }
             }
         }
@@ -158,14 +372,30 @@ impl MetadataGenerator {
     </manifest>
     <spine toc="ncx">"#);
         
+        // 封面页放在最前面
+        if epub.cover_image_path.is_some() {
+            content_opf.push_str(r#"
+        <itemref idref="cover" linear="yes"/>"#);
+        }
+        
+        // 标题页紧随封面页
+        content_opf.push_str(r#"
+        <itemref idref="title-page"/>"#);
+        
         // 添加章节到spine - 按卷的顺序添加
         for (i, volume) in epub.volumes.iter().enumerate() {
-            // 为有卷封面的卷添加章节0到spine
-            if volume.cover_image_path.is_some() {
+            // 卷首页作为每卷在spine中的分界
+            if volume.has_opening_page() {
                 content_opf.push_str(&format!(r#"
         <itemref idref="chapter{}_0"/>"#, i + 1));
             }
             
+            // 插图集紧随卷封面
+            if self.has_gallery(volume) {
+                content_opf.push_str(&format!(r#"
+        <itemref idref="gallery{}"/>"#, i + 1));
+            }
+            
             for (j, chapter) in volume.chapters.iter().enumerate() {
                 if chapter.xhtml_path.is_some() {
                     content_opf.push_str(&format!(r#"
@@ -175,30 +405,36 @@ impl MetadataGenerator {
         }
         
         content_opf.push_str(r#"
-    </spine>
-    <guide>"#);
+    </spine>"#);
         
-        // 添加封面指南
-        content_opf.push_str(r#"
-        <reference type="cover" title="Cover" href="images/cover.jpg"/>"#);
+        // 添加封面指南（guide不能为空，没有封面时整体省略）
+        if epub.cover_image_path.is_some() {
+            content_opf.push_str(r#"
+    <guide>
+        <reference type="cover" title="Cover" href="text/cover.xhtml"/>
+    </guide>"#);
+        }
         
         content_opf.push_str(r#"
-    </guide>
 </package>"#);
-        
-        fs::write(oebps_dir.join("content.opf"), content_opf)?;
-        Ok(())
+        content_opf
     }
 
     /// 生成toc.ncx文件
     pub fn generate_toc_ncx(&self, epub: &Epub, oebps_dir: &Path, novel_id: u32) -> Result<()> {
+        fs::write(oebps_dir.join("toc.ncx"), self.toc_ncx(epub, novel_id))?;
+        Ok(())
+    }
+
+    /// toc.ncx的内容
+    pub fn toc_ncx(&self, epub: &Epub, novel_id: u32) -> String {
         let mut toc_ncx = String::new();
         
         toc_ncx.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
 <ncx version="2005-1" xmlns="http://www.daisy.org/z3986/2005/ncx/">
     <head>
         <meta name="dtb:uid" content=""#);
-        toc_ncx.push_str(&format!("docln:{}", novel_id));
+        toc_ncx.push_str(&escape_xml(&self.book_identifier(novel_id)));
         toc_ncx.push_str(r#""/>
         <meta name="dtb:depth" content="1"/>
         <meta name="dtb:totalPageCount" content="0"/>
@@ -206,7 +442,7 @@ impl MetadataGenerator {
     </head>
     <docTitle>
         <text>"#);
-        toc_ncx.push_str(&epub.title);
+        toc_ncx.push_str(&escape_xml(&epub.title));
         toc_ncx.push_str(r#"</text>
     </docTitle>
     <navMap>"#);
@@ -219,12 +455,8 @@ impl MetadataGenerator {
                 .collect();
             
             if !processed_chapters.is_empty() {
-                // 确定卷的指向目标：如果有卷封面则指向章节0，否则指向第一个章节
-                let volume_target = if volume.cover_image_path.is_some() {
-                    format!("text/volume_{:03}/chapter_000.xhtml", volume_index + 1)
-                } else {
-                    processed_chapters.first().unwrap().xhtml_path.as_ref().unwrap().clone()
-                };
+                // 卷指向卷首页（章节0）
+                let volume_target = format!("text/volume_{:03}/chapter_000.xhtml", volume_index + 1);
                 
                 // 卷作为一级导航点
                 toc_ncx.push_str(&format!(r#"
@@ -233,9 +465,22 @@ impl MetadataGenerator {
                 <text>{}</text>
             </navLabel>
             <content src="{}"/>"#,
-                    nav_point_counter, nav_point_counter, volume.title, volume_target));
+                    nav_point_counter, nav_point_counter, escape_xml(&volume.title), volume_target));
                 nav_point_counter += 1;
                 
+                // 插图集作为卷的第一个子导航点
+                if self.has_gallery(volume) {
+                    toc_ncx.push_str(&format!(r#"
+            <navPoint id="navPoint{}" playOrder="{}">
+                <navLabel>
+                    <text>插图</text>
+                </navLabel>
+                <content src="text/volume_{:03}/gallery.xhtml"/>
+            </navPoint>"#,
+                        nav_point_counter, nav_point_counter, volume_index + 1));
+                    nav_point_counter += 1;
+                }
+                
                 // 章节作为卷的子导航点
                 for chapter in processed_chapters {
                     if let Some(xhtml_path) = &chapter.xhtml_path {
@@ -246,7 +491,7 @@ impl MetadataGenerator {
                 </navLabel>
                 <content src="{}"/>
             </navPoint>"#,
-                            nav_point_counter, nav_point_counter, chapter.title, xhtml_path));
+                            nav_point_counter, nav_point_counter, escape_xml(&chapter.title), xhtml_path));
                         nav_point_counter += 1;
                     }
                 }
@@ -259,9 +504,17 @@ impl MetadataGenerator {
         toc_ncx.push_str(r#"
     </navMap>
 </ncx>"#);
-        
-        fs::write(oebps_dir.join("toc.ncx"), toc_ncx)?;
-        Ok(())
+        toc_ncx
+    }
+
+    /// 元数据文件（container.xml、样式表、content.opf、toc.ncx）的 (相对工作目录的路径, 内容)，不含mimetype
+    pub fn metadata_files(&self, epub: &Epub, novel_id: u32) -> Vec<(String, String)> {
+        vec![
+            ("META-INF/container.xml".to_string(), CONTAINER_XML.to_string()),
+            ("OEBPS/styles/stylesheet.css".to_string(), STYLESHEET.to_string()),
+            ("OEBPS/content.opf".to_string(), self.content_opf(epub, novel_id)),
+            ("OEBPS/toc.ncx".to_string(), self.toc_ncx(epub, novel_id)),
+        ]
     }
 
     /// 生成所有元数据文件
@@ -276,10 +529,32 @@ impl MetadataGenerator {
         // 生成所有元数据文件
         self.generate_mimetype(epub_dir)?;
         self.generate_container_xml(&meta_inf_dir)?;
+        self.generate_stylesheet(&oebps_dir)?;
         self.generate_content_opf(epub, &oebps_dir, novel_id)?;
         self.generate_toc_ncx(epub, &oebps_dir, novel_id)?;
         
-        println!("EPUB元数据文件已生成");
+        debug!("EPUB元数据文件已生成");
         Ok(())
     }
+}
+
+/// 由小说ID得到的稳定书籍标识；同一小说重复生成时保持不变，阅读器可据此识别为同一本书
+pub fn default_identifier(novel_id: u32) -> String {
+    format!("urn:docln:{}", novel_id)
+}
+
+/// 根据文件扩展名确定图片的媒体类型
+pub fn media_type_for(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match extension.as_str() {
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => "image/jpeg",
+    }
 }
\ No newline at end of file
@@ -0,0 +1,52 @@
+use crate::error::{DoclnError, Result};
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use super::Epub;
+
+/// 用户提供的小说元数据覆盖，用于修正解析错误的作者、缺失的插画师等
+///
+/// 只有文件中出现的字段会覆盖解析结果，未出现的字段保持不变，例如：
+/// `{"author": "Saekisan", "illustrator": "Hanekoto", "tags": ["Romance"]}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetadataOverrides {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub illustrator: Option<String>,
+    pub genres: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+    pub language: Option<String>,
+}
+
+impl MetadataOverrides {
+    /// 从JSON文件加载覆盖内容
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| DoclnError::InvalidInput(format!("无法解析元数据覆盖文件 {}: {}", path.display(), e)))
+    }
+
+    /// 将覆盖内容写入小说信息
+    pub fn apply(&self, epub: &mut Epub) {
+        if let Some(title) = &self.title {
+            epub.title = title.clone();
+        }
+        if let Some(author) = &self.author {
+            epub.author = author.clone();
+        }
+        if let Some(illustrator) = &self.illustrator {
+            epub.illustrator = Some(illustrator.clone());
+        }
+        if let Some(genres) = &self.genres {
+            epub.genres = genres.clone();
+        }
+        if let Some(tags) = &self.tags {
+            epub.tags = tags.clone();
+        }
+        if let Some(language) = &self.language {
+            epub.language = language.clone();
+        }
+    }
+}
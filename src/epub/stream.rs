@@ -0,0 +1,145 @@
+use crate::error::{DoclnError, Result};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// 本身已压缩的图片格式，直接存储，再用Deflate压缩几乎没有收益
+const STORED_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp"];
+
+/// 可在多个任务间共享的增量EPUB写入器
+///
+/// 创建时立即写入未压缩的mimetype，之后章节、图片等内容可以在生成时
+/// 直接追加到ZIP中，无需先落盘再在压缩阶段重新读取。
+/// 图片直接存储，文本和XML使用Deflate压缩。
+///
+/// 设置了 [`work_dir`](Self::work_dir) 时，原本写入工作目录的文件按相对工作目录的路径存入ZIP，
+/// 爬虫的流式模式以此让章节和图片不经过磁盘。
+#[derive(Clone)]
+pub struct StreamingEpubWriter {
+    zip: Arc<Mutex<Option<ZipWriter<File>>>>,
+    entries: Arc<Mutex<Vec<String>>>,
+    path: PathBuf,
+    work_dir: Option<PathBuf>,
+    compression_level: Option<i64>,
+}
+
+impl StreamingEpubWriter {
+    pub fn new(epub_path: &Path) -> Result<Self> {
+        let file = File::create(epub_path)?;
+        let mut zip = ZipWriter::new(file);
+
+        // EPUB标准要求mimetype文件必须第一个添加且不压缩
+        zip.start_file("mimetype", entry_options().compression_method(CompressionMethod::Stored))?;
+        zip.write_all(b"application/epub+zip")?;
+
+        Ok(Self {
+            zip: Arc::new(Mutex::new(Some(zip))),
+            entries: Arc::new(Mutex::new(vec!["mimetype".to_string()])),
+            path: epub_path.to_path_buf(),
+            work_dir: None,
+            compression_level: None,
+        })
+    }
+
+    /// 设置对应的EPUB工作目录，工作目录下的文件路径据此换算为ZIP内的路径
+    pub fn work_dir(mut self, work_dir: impl Into<PathBuf>) -> Self {
+        self.work_dir = Some(work_dir.into());
+        self
+    }
+
+    /// 正在写入的EPUB文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 设置文本条目的Deflate压缩级别（0-9），None为默认级别
+    pub fn compression_level(mut self, compression_level: Option<i64>) -> Self {
+        self.compression_level = compression_level;
+        self
+    }
+
+    /// 追加一个文件到EPUB中，zip_path为ZIP内的路径（如 OEBPS/text/...）
+    pub fn add_file(&self, zip_path: &str, content: &[u8]) -> Result<()> {
+        let mut guard = self.zip.lock().map_err(|_| DoclnError::Epub("EPUB写入器锁已损坏".to_string()))?;
+        let zip = guard.as_mut().ok_or_else(|| DoclnError::Epub("EPUB写入器已完成，无法继续写入".to_string()))?;
+
+        // mimetype已在创建时写入
+        if zip_path == "mimetype" {
+            return Ok(());
+        }
+
+        let extension = Path::new(zip_path).extension().map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        let options = if extension.is_some_and(|ext| STORED_EXTENSIONS.contains(&ext.as_str())) {
+            entry_options().compression_method(CompressionMethod::Stored)
+        } else {
+            entry_options().compression_method(CompressionMethod::Deflated).compression_level(self.compression_level)
+        };
+        zip.start_file(zip_path, options)?;
+        zip.write_all(content)?;
+        drop(guard);
+
+        self.entries.lock().map_err(|_| DoclnError::Epub("EPUB写入器锁已损坏".to_string()))?.push(zip_path.to_string());
+        Ok(())
+    }
+
+    /// 按工作目录下的路径追加文件，路径不在工作目录下时返回错误
+    pub fn add_work_file(&self, path: &Path, content: &[u8]) -> Result<()> {
+        let zip_path = self.zip_path(path)?;
+        self.add_file(&zip_path, content)
+    }
+
+    /// ZIP中是否已有该条目
+    pub fn contains(&self, zip_path: &str) -> bool {
+        self.entries.lock().map(|entries| entries.iter().any(|entry| entry == zip_path)).unwrap_or(false)
+    }
+
+    /// 工作目录下的文件是否已写入
+    pub fn contains_work_file(&self, path: &Path) -> bool {
+        self.zip_path(path).is_ok_and(|zip_path| self.contains(&zip_path))
+    }
+
+    /// 工作目录下的文件在ZIP中的路径，统一使用 `/` 分隔
+    fn zip_path(&self, path: &Path) -> Result<String> {
+        let work_dir = self.work_dir.as_deref().ok_or_else(|| DoclnError::Epub("EPUB写入器未设置工作目录".to_string()))?;
+        let relative = path
+            .strip_prefix(work_dir)
+            .map_err(|_| DoclnError::Epub(format!("{} 不在工作目录 {} 下", path.display(), work_dir.display())))?;
+        Ok(relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+    }
+
+    /// 已写入的文件列表（按写入顺序）
+    pub fn entries(&self) -> Vec<String> {
+        self.entries.lock().map(|entries| entries.clone()).unwrap_or_default()
+    }
+
+    /// 完成写入并返回EPUB文件路径
+    pub fn finish(&self) -> Result<PathBuf> {
+        let mut guard = self.zip.lock().map_err(|_| DoclnError::Epub("EPUB写入器锁已损坏".to_string()))?;
+        if let Some(zip) = guard.take() {
+            zip.finish()?;
+        }
+        Ok(self.path.clone())
+    }
+}
+
+/// ZIP条目的选项：使用固定的修改时间（1980-01-01），相同的内容总是生成相同的字节
+fn entry_options() -> FileOptions<'static, ()> {
+    FileOptions::default().last_modified_time(zip::DateTime::default())
+}
+
+/// 写入工作目录下的文件：有流式写入器时存入EPUB，否则写入磁盘（自动创建上级目录）
+pub(crate) fn write_work_file(stream: Option<&StreamingEpubWriter>, path: &Path, content: &[u8]) -> Result<()> {
+    match stream {
+        Some(stream) => stream.add_work_file(path, content),
+        None => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, content)?;
+            Ok(())
+        }
+    }
+}
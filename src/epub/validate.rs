@@ -0,0 +1,75 @@
+use crate::error::{DoclnError, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use percent_encoding::percent_decode_str;
+use log::debug;
+
+/// 打包前校验content.opf与实际文件是否一致
+///
+/// 检查manifest中每个item的href都指向OEBPS下存在的文件、spine中的每个idref都能在manifest中找到、
+/// 封面引用（meta cover和guide）有效。发现问题时返回列出全部问题条目的错误。
+pub fn validate(oebps_dir: &Path) -> Result<()> {
+    let content_opf = fs::read_to_string(oebps_dir.join("content.opf"))?;
+    validate_package(&content_opf, |href| oebps_dir.join(href).is_file())
+}
+
+/// 按content.opf的内容校验，exists判断OEBPS下的相对路径是否存在，供文件尚未落盘的流式打包使用
+///
+/// content.opf按XML解析，格式不正确时直接报错；href按URL解码（%20等）后再查找文件。
+pub fn validate_package(content_opf: &str, exists: impl Fn(&str) -> bool) -> Result<()> {
+    let document = roxmltree::Document::parse(content_opf)
+        .map_err(|e| DoclnError::Epub(format!("EPUB校验失败: content.opf不是格式正确的XML: {}", e)))?;
+    // 按父元素筛选，例如只有manifest下的item才是资源条目
+    let children_of = |parent: &'static str, name: &'static str| {
+        document
+            .descendants()
+            .filter(move |node| node.has_tag_name(name) && node.parent_element().is_some_and(|p| p.has_tag_name(parent)))
+    };
+    let href_exists = |href: &str| !href.is_empty() && exists(&percent_decode_str(href).decode_utf8_lossy());
+
+    let mut problems = Vec::new();
+    let mut manifest_ids = HashSet::new();
+
+    for item in children_of("manifest", "item") {
+        let id = item.attribute("id").unwrap_or("");
+        let href = item.attribute("href").unwrap_or("");
+        if id.is_empty() {
+            problems.push(format!("manifest条目缺少id (href: {})", href));
+        } else if !manifest_ids.insert(id.to_string()) {
+            problems.push(format!("manifest中的id重复: {}", id));
+        }
+        if !href_exists(href) {
+            problems.push(format!("manifest条目 '{}' 指向的文件不存在: {}", id, href));
+        }
+    }
+
+    for itemref in children_of("spine", "itemref") {
+        let idref = itemref.attribute("idref").unwrap_or("");
+        if !manifest_ids.contains(idref) {
+            problems.push(format!("spine引用了不存在的manifest id: {}", idref));
+        }
+    }
+
+    for meta in children_of("metadata", "meta").filter(|meta| meta.attribute("name") == Some("cover")) {
+        let content = meta.attribute("content").unwrap_or("");
+        if !manifest_ids.contains(content) {
+            problems.push(format!("封面meta引用了不存在的manifest id: {}", content));
+        }
+    }
+
+    for reference in children_of("guide", "reference") {
+        let href = reference.attribute("href").unwrap_or("");
+        let path = href.split('#').next().unwrap_or("");
+        if !href_exists(path) {
+            problems.push(format!("guide引用的文件不存在: {}", href));
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(DoclnError::Epub(format!("EPUB校验失败:\n  - {}", problems.join("\n  - "))));
+    }
+
+    debug!("EPUB校验通过");
+    Ok(())
+}
@@ -1,22 +1,33 @@
-use anyhow::Result;
+use crate::error::Result;
 use std::fs;
 use std::path::Path;
 use serde::{Serialize, Deserialize};
 use crate::epub::chapter::Chapter;
+use crate::epub::IMAGES_DIR;
+use crate::utils::escape_xml;
+use log::debug;
 
 pub struct VolumeBuilder {
     title: String,
     volume_id: String,
     cover_image_path: Option<String>,
+    illustrator: Option<String>,
     chapters: Vec<Chapter>,
 }
 
+impl Default for VolumeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl VolumeBuilder {
     pub fn new() -> Self {
         Self {
             title: String::new(),
             volume_id: String::new(),
             cover_image_path: None,
+            illustrator: None,
             chapters: Vec::new(),
         }
     }
@@ -36,6 +47,11 @@ impl VolumeBuilder {
         self
     }
 
+    pub fn illustrator(mut self, illustrator: Option<String>) -> Self {
+        self.illustrator = illustrator;
+        self
+    }
+
     pub fn chapters(mut self, chapters: Vec<Chapter>) -> Self {
         self.chapters = chapters;
         self
@@ -46,6 +62,7 @@ impl VolumeBuilder {
             title: self.title,
             volume_id: self.volume_id,
             cover_image_path: self.cover_image_path,
+            illustrator: self.illustrator,
             chapters: self.chapters,
         }
     }
@@ -56,6 +73,8 @@ pub struct Volume {
     pub title: String,
     pub volume_id: String,
     pub cover_image_path: Option<String>,
+    #[serde(default)]
+    pub illustrator: Option<String>, // 该卷单独标注的插画师，未标注时为None，使用小说的插画师
     pub chapters: Vec<Chapter>,
 }
 
@@ -64,48 +83,211 @@ impl Volume {
         VolumeBuilder::new()
     }
 
-    pub fn generate_volume_cover_chapter(&self, volume_index: usize, oebps_dir: &Path) -> Result<()> {
+    /// 该卷的插画师：优先使用卷单独标注的，否则为小说的插画师
+    pub fn illustrator_or<'a>(&'a self, novel_illustrator: Option<&'a str>) -> Option<&'a str> {
+        self.illustrator.as_deref().or(novel_illustrator)
+    }
+
+    /// 卷首页中显示卷插画师的段落，只在该卷单独标注了插画师时生成
+    fn illustrator_paragraph(&self) -> String {
+        self.illustrator
+            .as_ref()
+            .map(|illustrator| format!("        <p class=\"volume-illustrator\">插画师: {}</p>\n", escape_xml(illustrator)))
+            .unwrap_or_default()
+    }
+
+    /// 该卷是否有卷首页（chapter_000.xhtml）：有卷封面时为封面页，否则有已下载章节时为卷标题分隔页
+    pub fn has_opening_page(&self) -> bool {
+        self.cover_image_path.is_some() || self.chapters.iter().any(|chapter| chapter.xhtml_path.is_some())
+    }
+
+    pub fn generate_volume_cover_chapter(&self, volume_index: usize, oebps_dir: &Path, language: &str) -> Result<()> {
         let volume_dir = oebps_dir.join("text").join(format!("volume_{:03}", volume_index + 1));
         fs::create_dir_all(&volume_dir)?;
 
-        let chapter_filename = format!("chapter_000.xhtml");
+        let chapter_filename = "chapter_000.xhtml".to_string();
         let chapter_path = volume_dir.join(chapter_filename);
+        fs::write(&chapter_path, self.cover_chapter_xhtml(language))?;
+        debug!("卷 '{}' 封面章节已生成: {}", self.title, chapter_path.display());
+        Ok(())
+    }
 
+    /// 卷封面页（chapter_000.xhtml）的内容：卷标题和整页显示的卷封面，language写入xml:lang
+    pub fn cover_chapter_xhtml(&self, language: &str) -> String {
         let mut xhtml_content = String::new();
         xhtml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
-<html xmlns="http://www.w3.org/1999/xhtml">
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang=""#);
+        xhtml_content.push_str(&escape_xml(language));
+        xhtml_content.push_str(r#"">
 <head>
     <title>"#);
 
-        xhtml_content.push_str(&self.title);
+        xhtml_content.push_str(&escape_xml(&self.title));
         xhtml_content.push_str(r#"</title>
     <meta http-equiv="Content-Type" content="text/html; charset=UTF-8"/>
+    <link rel="stylesheet" type="text/css" href="../../styles/stylesheet.css"/>
 </head>
-<body>
-    <div class="cover">
-        <h1>"#);
-
-        xhtml_content.push_str(&self.title);
-        xhtml_content.push_str(r#"</h1>
-"#);
+<body class="cover-page" epub:type="cover">
+    <div class="cover" epub:type="titlepage">
+        <h1 class="volume-cover-title">"#);
+        xhtml_content.push_str(&escape_xml(&self.title));
+        xhtml_content.push_str("</h1>\n");
 
-        // 插入封面图片
+        // 插入封面图片，标题作为替代文本，样式由样式表控制
         if let Some(ref cover_path) = self.cover_image_path {
             // 计算相对路径（假设cover_path已是相对OEBPS的路径）
             xhtml_content.push_str(&format!(
-                "        <img src=\"../../{}\" alt=\"封面\" class=\"volume-cover-img\"/>",
-                cover_path
+                "        <img src=\"../../{}\" alt=\"{}\" class=\"volume-cover-img\"/>",
+                cover_path, escape_xml(&self.title)
             ));
-            xhtml_content.push_str("\n");
+            xhtml_content.push('\n');
         }
+        xhtml_content.push_str(&self.illustrator_paragraph());
 
         xhtml_content.push_str(r#"    </div>
 </body>
 </html>"#);
+        xhtml_content
+    }
 
-        fs::write(&chapter_path, xhtml_content)?;
-        println!("卷 '{}' 封面章节已生成: {}", self.title, chapter_path.display());
+    /// 为没有卷封面的卷生成只含卷标题的分隔页（chapter_000.xhtml），按顺序阅读时各卷之间有明确的分界
+    pub fn generate_volume_title_chapter(&self, volume_index: usize, oebps_dir: &Path, language: &str) -> Result<()> {
+        let volume_dir = oebps_dir.join("text").join(format!("volume_{:03}", volume_index + 1));
+        fs::create_dir_all(&volume_dir)?;
+        let chapter_path = volume_dir.join("chapter_000.xhtml");
+        fs::write(&chapter_path, self.title_chapter_xhtml(language))?;
+        debug!("卷 '{}' 标题页已生成: {}", self.title, chapter_path.display());
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// 卷标题分隔页（chapter_000.xhtml）的内容，language写入xml:lang
+    pub fn title_chapter_xhtml(&self, language: &str) -> String {
+        let mut xhtml_content = String::new();
+        xhtml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang=""#);
+        xhtml_content.push_str(&escape_xml(language));
+        xhtml_content.push_str(r#"">
+<head>
+    <title>"#);
+        xhtml_content.push_str(&escape_xml(&self.title));
+        xhtml_content.push_str(r#"</title>
+    <meta http-equiv="Content-Type" content="text/html; charset=UTF-8"/>
+    <link rel="stylesheet" type="text/css" href="../../styles/stylesheet.css"/>
+</head>
+<body class="volume-title-page">
+    <div class="volume-title" epub:type="part">
+        <h1>"#);
+        xhtml_content.push_str(&escape_xml(&self.title));
+        xhtml_content.push_str("</h1>\n");
+        xhtml_content.push_str(&self.illustrator_paragraph());
+        xhtml_content.push_str(r#"    </div>
+</body>
+</html>"#);
+        xhtml_content
+    }
+
+    /// 卷首页（chapter_000.xhtml）的内容：有卷封面时为封面页，否则有已下载章节时为卷标题分隔页，都没有时为None
+    pub fn opening_page_xhtml(&self, language: &str) -> Option<String> {
+        if self.cover_image_path.is_some() {
+            Some(self.cover_chapter_xhtml(language))
+        } else if self.has_opening_page() {
+            Some(self.title_chapter_xhtml(language))
+        } else {
+            None
+        }
+    }
+
+    /// 按章节顺序汇总该卷已下载的插图（相对OEBPS目录的路径）
+    pub fn illustration_paths(&self) -> Vec<String> {
+        self.chapters
+            .iter()
+            .flat_map(|chapter| chapter.illustration_paths.iter().cloned())
+            .collect()
+    }
+
+    /// 生成卷插图集页面，每张插图单独一页；该卷没有插图时返回false
+    pub fn generate_gallery_chapter(&self, volume_index: usize, oebps_dir: &Path, language: &str) -> Result<bool> {
+        let Some(xhtml_content) = self.gallery_xhtml(language) else {
+            return Ok(false);
+        };
+
+        let volume_dir = oebps_dir.join("text").join(format!("volume_{:03}", volume_index + 1));
+        fs::create_dir_all(&volume_dir)?;
+        let gallery_path = volume_dir.join("gallery.xhtml");
+        fs::write(&gallery_path, xhtml_content)?;
+        debug!("卷 '{}' 插图集已生成: {}", self.title, gallery_path.display());
+        Ok(true)
+    }
+
+    /// 卷插图集页面的内容，插图按章节顺序排列，language写入xml:lang；该卷没有插图时为None
+    pub fn gallery_xhtml(&self, language: &str) -> Option<String> {
+        let illustrations = self.illustration_paths();
+        if illustrations.is_empty() {
+            return None;
+        }
+
+        let mut xhtml_content = String::new();
+        xhtml_content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html PUBLIC "-//W3C//DTD XHTML 1.1//EN" "http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd">
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang=""#);
+        xhtml_content.push_str(&escape_xml(language));
+        xhtml_content.push_str(r#"">
+<head>
+    <title>"#);
+        xhtml_content.push_str(&escape_xml(&self.title));
+        xhtml_content.push_str(r#"</title>
+    <meta http-equiv="Content-Type" content="text/html; charset=UTF-8"/>
+    <link rel="stylesheet" type="text/css" href="../../styles/stylesheet.css"/>
+</head>
+<body class="cover-page">
+"#);
+
+        for (i, illustration) in illustrations.iter().enumerate() {
+            xhtml_content.push_str(&format!(
+                "    <div class=\"cover gallery-item\">\n        <img src=\"../../{}\" alt=\"插图 {}\" class=\"volume-cover-img\"/>\n    </div>\n",
+                illustration, i + 1
+            ));
+        }
+
+        xhtml_content.push_str(r#"</body>
+</html>"#);
+        Some(xhtml_content)
+    }
+}
+/// 按文件名顺序扫描单个章节已下载到磁盘的插图（相对OEBPS目录的路径），用于续传时找回跳过章节的插图
+///
+/// 同时识别两种存放方式：默认的 `images/volume_XXX/chapter_XXX/NNN.ext`，
+/// 以及扁平存放的 `images/vXXX_cXXX_iNNN.ext`。
+pub fn chapter_illustration_paths(volume_index: usize, chapter_index: usize, images_dir: &Path) -> Vec<String> {
+    let mut paths = Vec::new();
+
+    let nested_dir = format!("volume_{:03}/chapter_{:03}", volume_index + 1, chapter_index + 1);
+    let mut nested_names = image_file_names(&images_dir.join(&nested_dir));
+    nested_names.sort();
+    paths.extend(nested_names.into_iter().map(|name| format!("{}/{}/{}", IMAGES_DIR, nested_dir, name)));
+
+    let flat_prefix = format!("v{:03}_c{:03}_i", volume_index + 1, chapter_index + 1);
+    let mut flat_names: Vec<String> = image_file_names(images_dir)
+        .into_iter()
+        .filter(|name| name.starts_with(&flat_prefix))
+        .collect();
+    flat_names.sort();
+    paths.extend(flat_names.into_iter().map(|name| format!("{}/{}", IMAGES_DIR, name)));
+
+    paths
+}
+
+/// 列出目录下的图片文件名，目录不存在时返回空
+fn image_file_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+        .filter(|name| [".jpeg", ".jpg", ".png", ".gif", ".webp"].iter().any(|ext| name.ends_with(ext)))
+        .collect()
+}
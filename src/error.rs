@@ -0,0 +1,68 @@
+use std::time::Duration;
+use thiserror::Error;
+
+/// docln-fetch的错误类型
+#[derive(Debug, Error)]
+pub enum DoclnError {
+    /// 小说或页面不存在
+    #[error("未找到: {0}")]
+    NotFound(String),
+
+    /// 网络请求失败（连接、超时、错误状态码等）
+    #[error("网络错误: {0}")]
+    Network(#[from] reqwest::Error),
+
+    /// 站点返回429，请求过于频繁
+    #[error("请求过于频繁，已被站点限流 (HTTP 429)，{}", retry_hint(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// 返回了Cloudflare验证页面
+    #[error("请求被Cloudflare拦截（返回了验证页面），请稍后重试或降低请求频率")]
+    BlockedByCloudflare,
+
+    /// 章节仅限登录会员阅读，站点返回了登录页面
+    #[error("需要登录才能访问: {0}（请通过cookie提供登录会话）")]
+    LoginRequired(String),
+
+    /// 开启robots.txt检查时，页面被robots.txt禁止访问
+    #[error("robots.txt 不允许访问: {0}")]
+    DisallowedByRobots(String),
+
+    /// 页面结构不符合预期
+    #[error("解析错误: {0}")]
+    Parse(String),
+
+    /// 图片无效或不符合限制
+    #[error("图片错误: {0}")]
+    Image(String),
+
+    /// 用户输入或命令行参数无效
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("IO错误: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON错误: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("ZIP错误: {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    /// 操作被调用方取消（如章节事件的接收端已关闭）
+    #[error("操作已取消")]
+    Cancelled,
+
+    /// EPUB生成失败
+    #[error("EPUB生成错误: {0}")]
+    Epub(String),
+}
+
+fn retry_hint(retry_after: &Option<Duration>) -> String {
+    match retry_after {
+        Some(retry_after) => format!("请在 {} 秒后重试并降低请求频率", retry_after.as_secs()),
+        None => "请降低请求频率后重试".to_string(),
+    }
+}
+
+pub type Result<T> = std::result::Result<T, DoclnError>;
@@ -0,0 +1,9 @@
+pub mod json;
+pub mod cbz;
+pub mod text;
+pub mod markdown;
+
+pub use json::JsonExporter;
+pub use cbz::CbzExporter;
+pub use text::TextExporter;
+pub use markdown::MarkdownExporter;
@@ -0,0 +1,98 @@
+use crate::error::{DoclnError, Result};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::ZipWriter;
+use zip::write::FileOptions;
+use crate::epub::Epub;
+use log::{info, warn};
+
+/// 将已下载的插图按卷打包为CBZ（漫画压缩包），适合以插图为主的阅读方式
+///
+/// 复用爬取时下载到 OEBPS/images/ 下的图片，每卷生成一个CBZ，
+/// 卷封面在前，其后按章节和插图编号排序。
+pub struct CbzExporter {
+    epub: Epub,
+    epub_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+}
+
+impl CbzExporter {
+    pub fn new(epub: Epub) -> Self {
+        Self {
+            epub,
+            epub_dir: None,
+            output_dir: None,
+        }
+    }
+
+    /// 设置爬取时使用的EPUB工作目录（包含OEBPS的目录）
+    pub fn epub_dir<P: Into<PathBuf>>(mut self, epub_dir: P) -> Self {
+        self.epub_dir = Some(epub_dir.into());
+        self
+    }
+
+    /// 设置CBZ输出目录，默认为工作目录的上级目录
+    pub fn output_dir<P: Into<PathBuf>>(mut self, output_dir: P) -> Self {
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    /// 生成CBZ文件，返回生成的文件路径；没有插图的卷会被跳过
+    pub fn generate(self) -> Result<Vec<String>> {
+        let epub_dir = self.epub_dir.ok_or_else(|| DoclnError::Epub("EPUB directory is required".to_string()))?;
+        let oebps_dir = epub_dir.join("OEBPS");
+        let output_dir = self
+            .output_dir
+            .or_else(|| epub_dir.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."));
+        fs::create_dir_all(&output_dir)?;
+
+        let mut generated = Vec::new();
+        for (volume_index, volume) in self.epub.volumes.iter().enumerate() {
+            let mut pages = Vec::new();
+            if let Some(cover_path) = &volume.cover_image_path {
+                pages.push(cover_path.clone());
+            }
+            pages.extend(volume.illustration_paths());
+
+            if pages.is_empty() {
+                continue;
+            }
+
+            let cbz_path = output_dir.join(format!("docln_{}_vol{:03}.cbz", self.epub.id, volume_index + 1));
+            write_cbz(&cbz_path, &oebps_dir, &pages)?;
+            info!("卷 '{}' CBZ文件生成成功: {} ({} 页)", volume.title, cbz_path.display(), pages.len());
+            generated.push(cbz_path.to_string_lossy().to_string());
+        }
+
+        if generated.is_empty() {
+            warn!("没有找到任何插图，未生成CBZ文件");
+        }
+        Ok(generated)
+    }
+}
+
+/// 按顺序写入图片，文件名使用页码保证阅读器中的顺序
+fn write_cbz(cbz_path: &Path, oebps_dir: &Path, pages: &[String]) -> Result<()> {
+    let mut zip = ZipWriter::new(File::create(cbz_path)?);
+    // 图片本身已压缩，直接存储
+    let options: FileOptions<'_, ()> = FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    for (page_index, page) in pages.iter().enumerate() {
+        let source = oebps_dir.join(page);
+        let extension = Path::new(page).extension().and_then(|ext| ext.to_str()).unwrap_or("jpg");
+        let content = match fs::read(&source) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("读取图片失败，已跳过: {} ({})", source.display(), e);
+                continue;
+            }
+        };
+        zip.start_file(format!("{:04}.{}", page_index + 1, extension), options)?;
+        zip.write_all(&content)?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
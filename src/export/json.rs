@@ -0,0 +1,44 @@
+use crate::error::Result;
+use std::fs;
+use std::path::Path;
+use crate::epub::Epub;
+use log::info;
+
+/// 将爬取到的小说信息导出为JSON，供不需要EPUB的用户自行处理
+pub struct JsonExporter {
+    epub: Epub,
+    output_path: Option<String>,
+}
+
+impl JsonExporter {
+    pub fn new(epub: Epub) -> Self {
+        Self {
+            epub,
+            output_path: None,
+        }
+    }
+
+    /// 设置输出文件路径，默认为 docln_{id}.json
+    pub fn output_path<S: Into<String>>(mut self, output_path: S) -> Self {
+        self.output_path = Some(output_path.into());
+        self
+    }
+
+    pub fn generate(self) -> Result<String> {
+        let output_path = self
+            .output_path
+            .unwrap_or_else(|| format!("docln_{}.json", self.epub.id));
+
+        if let Some(parent) = Path::new(&output_path).parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json_content = serde_json::to_string_pretty(&self.epub)?;
+        fs::write(&output_path, json_content)?;
+
+        info!("JSON文件生成成功: {}", output_path);
+        Ok(output_path)
+    }
+}
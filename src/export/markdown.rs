@@ -0,0 +1,100 @@
+use crate::error::{DoclnError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::epub::Epub;
+use super::text::{read_chapter_blocks, ChapterBlock};
+use log::{info, warn};
+
+/// 将已爬取的章节按卷导出为Markdown文件
+///
+/// 每卷生成一个 docln_{id}_volXXX.md，卷标题为一级标题、章节标题为二级标题；
+/// 插图复制到 docln_{id}_images/ 下并以 `![](...)` 引用，导出结果不依赖EPUB工作目录。
+pub struct MarkdownExporter {
+    epub: Epub,
+    epub_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+}
+
+impl MarkdownExporter {
+    pub fn new(epub: Epub) -> Self {
+        Self {
+            epub,
+            epub_dir: None,
+            output_dir: None,
+        }
+    }
+
+    /// 设置爬取时使用的EPUB工作目录（包含OEBPS的目录）
+    pub fn epub_dir<P: Into<PathBuf>>(mut self, epub_dir: P) -> Self {
+        self.epub_dir = Some(epub_dir.into());
+        self
+    }
+
+    /// 设置Markdown输出目录，默认为工作目录的上级目录
+    pub fn output_dir<P: Into<PathBuf>>(mut self, output_dir: P) -> Self {
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    /// 生成Markdown文件，返回生成的文件路径；没有已下载章节的卷会被跳过
+    pub fn generate(self) -> Result<Vec<String>> {
+        let epub_dir = self.epub_dir.ok_or_else(|| DoclnError::Epub("EPUB directory is required".to_string()))?;
+        let oebps_dir = epub_dir.join("OEBPS");
+        let output_dir = self
+            .output_dir
+            .or_else(|| epub_dir.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."));
+        fs::create_dir_all(&output_dir)?;
+        let images_dir_name = format!("docln_{}_images", self.epub.id);
+
+        let mut generated = Vec::new();
+        for (volume_index, volume) in self.epub.volumes.iter().enumerate() {
+            let mut content = format!("# {}\n", volume.title);
+            let mut chapter_count = 0;
+
+            for chapter in volume.chapters.iter().filter(|chapter| chapter.xhtml_path.is_some()) {
+                let blocks = match read_chapter_blocks(&oebps_dir, chapter) {
+                    Ok(blocks) => blocks,
+                    Err(e) => {
+                        warn!("读取章节 '{}' 失败，已跳过: {}", chapter.title, e);
+                        continue;
+                    }
+                };
+                content.push_str(&format!("\n## {}\n", chapter.title));
+                for block in blocks {
+                    match block {
+                        ChapterBlock::Paragraph(text) => content.push_str(&format!("\n{}\n", text)),
+                        ChapterBlock::Image { path, alt } => {
+                            // 去掉OEBPS中的images/前缀，保留其下的目录结构
+                            let relative_path = path.strip_prefix("images/").unwrap_or(&path);
+                            let target = output_dir.join(&images_dir_name).join(relative_path);
+                            if let Some(parent) = target.parent() {
+                                fs::create_dir_all(parent)?;
+                            }
+                            if let Err(e) = fs::copy(oebps_dir.join(&path), &target) {
+                                warn!("复制插图失败，已跳过: {} ({})", path, e);
+                                continue;
+                            }
+                            content.push_str(&format!("\n![{}]({}/{})\n", alt, images_dir_name, relative_path));
+                        }
+                    }
+                }
+                chapter_count += 1;
+            }
+
+            if chapter_count == 0 {
+                continue;
+            }
+
+            let markdown_path = output_dir.join(format!("docln_{}_vol{:03}.md", self.epub.id, volume_index + 1));
+            fs::write(&markdown_path, content)?;
+            info!("卷 '{}' Markdown文件生成成功: {} ({} 章)", volume.title, markdown_path.display(), chapter_count);
+            generated.push(markdown_path.to_string_lossy().to_string());
+        }
+
+        if generated.is_empty() {
+            warn!("没有找到任何已下载的章节，未生成Markdown文件");
+        }
+        Ok(generated)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::error::{DoclnError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use scraper::{Html, Selector};
+use crate::epub::{Chapter, Epub};
+use log::{info, warn};
+
+/// 章节正文中的一个块：文字段落或插图
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ChapterBlock {
+    Paragraph(String),
+    Image { path: String, alt: String }, // path为相对OEBPS目录的路径
+}
+
+/// 从爬取时生成的章节XHTML中按顺序读出文字段落、插图和脚注
+///
+/// 章节XHTML已经过段落提取、广告过滤和脚注处理，这里直接复用其结果而不是重新请求页面。
+pub(crate) fn read_chapter_blocks(oebps_dir: &Path, chapter: &Chapter) -> Result<Vec<ChapterBlock>> {
+    let xhtml_path = chapter
+        .xhtml_path
+        .as_ref()
+        .ok_or_else(|| DoclnError::NotFound(format!("章节 '{}' 没有已下载的内容", chapter.title)))?;
+    let content = fs::read_to_string(oebps_dir.join(xhtml_path))?;
+    let document = Html::parse_document(&content);
+    let p_selector = Selector::parse("div.chapter-content p").unwrap();
+    let img_selector = Selector::parse("img").unwrap();
+    let footnote_selector = Selector::parse("div.footnotes aside").unwrap();
+
+    let mut blocks = Vec::new();
+    for p_element in document.select(&p_selector) {
+        for img_element in p_element.select(&img_selector) {
+            let Some(src) = img_element.value().attr("src") else {
+                continue;
+            };
+            // 章节位于text/volume_XXX/下，图片地址形如 ../../images/...
+            blocks.push(ChapterBlock::Image {
+                path: src.trim_start_matches("../").to_string(),
+                alt: img_element.value().attr("alt").unwrap_or("").to_string(),
+            });
+        }
+        let text = p_element.text().collect::<String>().trim().to_string();
+        if !text.is_empty() {
+            blocks.push(ChapterBlock::Paragraph(text));
+        }
+    }
+
+    // 脚注放在章节末尾，保持 "[n] 内容" 的形式
+    for footnote in document.select(&footnote_selector) {
+        let text = footnote.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+        if let Some((number, note)) = text.split_once(". ") {
+            blocks.push(ChapterBlock::Paragraph(format!("[{}] {}", number, note)));
+        } else if !text.is_empty() {
+            blocks.push(ChapterBlock::Paragraph(text));
+        }
+    }
+    Ok(blocks)
+}
+
+/// 将已爬取的章节按卷导出为纯文本文件，适合快速阅读或全文检索
+///
+/// 每卷生成一个 docln_{id}_volXXX.txt，包含卷标题、章节标题和正文段落，插图以占位文字表示。
+pub struct TextExporter {
+    epub: Epub,
+    epub_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+}
+
+impl TextExporter {
+    pub fn new(epub: Epub) -> Self {
+        Self {
+            epub,
+            epub_dir: None,
+            output_dir: None,
+        }
+    }
+
+    /// 设置爬取时使用的EPUB工作目录（包含OEBPS的目录）
+    pub fn epub_dir<P: Into<PathBuf>>(mut self, epub_dir: P) -> Self {
+        self.epub_dir = Some(epub_dir.into());
+        self
+    }
+
+    /// 设置文本输出目录，默认为工作目录的上级目录
+    pub fn output_dir<P: Into<PathBuf>>(mut self, output_dir: P) -> Self {
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    /// 生成文本文件，返回生成的文件路径；没有已下载章节的卷会被跳过
+    pub fn generate(self) -> Result<Vec<String>> {
+        let epub_dir = self.epub_dir.ok_or_else(|| DoclnError::Epub("EPUB directory is required".to_string()))?;
+        let oebps_dir = epub_dir.join("OEBPS");
+        let output_dir = self
+            .output_dir
+            .or_else(|| epub_dir.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."));
+        fs::create_dir_all(&output_dir)?;
+
+        let mut generated = Vec::new();
+        for (volume_index, volume) in self.epub.volumes.iter().enumerate() {
+            let mut content = format!("{}\n{}\n{}\n", self.epub.title, volume.title, "=".repeat(40));
+            let mut chapter_count = 0;
+
+            for chapter in volume.chapters.iter().filter(|chapter| chapter.xhtml_path.is_some()) {
+                let blocks = match read_chapter_blocks(&oebps_dir, chapter) {
+                    Ok(blocks) => blocks,
+                    Err(e) => {
+                        warn!("读取章节 '{}' 失败，已跳过: {}", chapter.title, e);
+                        continue;
+                    }
+                };
+                content.push_str(&format!("\n\n{}\n{}\n", chapter.title, "-".repeat(40)));
+                for block in blocks {
+                    match block {
+                        ChapterBlock::Paragraph(text) => content.push_str(&format!("\n{}\n", text)),
+                        ChapterBlock::Image { .. } => content.push_str("\n[插图]\n"),
+                    }
+                }
+                chapter_count += 1;
+            }
+
+            if chapter_count == 0 {
+                continue;
+            }
+
+            let text_path = output_dir.join(format!("docln_{}_vol{:03}.txt", self.epub.id, volume_index + 1));
+            fs::write(&text_path, content)?;
+            info!("卷 '{}' 文本文件生成成功: {} ({} 章)", volume.title, text_path.display(), chapter_count);
+            generated.push(text_path.to_string_lossy().to_string());
+        }
+
+        if generated.is_empty() {
+            warn!("没有找到任何已下载的章节，未生成文本文件");
+        }
+        Ok(generated)
+    }
+}
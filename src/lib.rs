@@ -1,7 +1,17 @@
+#[cfg(feature = "cli")]
+pub mod cli;
 pub mod crawler;
 pub mod epub;
+pub mod error;
+pub mod export;
 pub mod utils;
 
-pub use crawler::DoclnCrawler;
-pub use epub::{Epub, Volume, Chapter, EpubGenerator};
-pub use utils::get_user_input;
\ No newline at end of file
+pub use crawler::{ChapterEvent, CrawlStats, DoclnCrawler, DoclnCrawlerBuilder, HealthStatus, NovelCategory, OutputFormat, VolumeSummary};
+pub use error::{DoclnError, Result};
+pub use epub::{Epub, NovelStatus, Volume, Chapter, EpubGenerator, FilenameSuffix, MetadataOverrides, ReadingDirection};
+pub use export::{CbzExporter, JsonExporter, MarkdownExporter, TextExporter};
+pub use utils::{escape_xml, load_title_overrides, normalize_url, normalize_whitespace};
+#[cfg(feature = "image-resize")]
+pub use crawler::ImageResizeOptions;
+#[cfg(feature = "cli")]
+pub use cli::{get_user_input, parse_cli_args, run_interactive, run_list_volumes, CliArgs};
\ No newline at end of file
@@ -1,32 +1,47 @@
-use anyhow::Result;
-use std::io::{self, Write};
-use docln_fetch::{DoclnCrawler, get_user_input};
+use docln_fetch::Result;
+use docln_fetch::{DoclnCrawler, EpubGenerator, OutputFormat, ReadingDirection, parse_cli_args, run_interactive, run_list_volumes};
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
-    let crawler = DoclnCrawler::new();
+    // 默认输出info及以上级别的日志，可通过RUST_LOG环境变量调整
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp(None)
+        .init();
     
-    loop {
-        println!("\n=== docln-fetch ===");
-        match get_user_input() {
-            Ok(novel_id) => {
-                println!("\n正在爬取 ID为 {} 的小说...", novel_id);
-                crawler.crawl_novel(novel_id).await;
-            }
-            Err(e) => {
-                println!("输入错误: {}", e);
-            }
+    let cli_args = parse_cli_args(std::env::args().skip(1))?;
+    if let Some(work_dir) = cli_args.as_ref().and_then(|cli_args| cli_args.compress.as_ref()) {
+        let epub_filename = EpubGenerator::compress_only(work_dir)?;
+        println!("EPUB文件已生成: {}", epub_filename);
+        return Ok(());
+    }
+    let mut builder = DoclnCrawler::builder()
+        .show_progress(true)
+        .verbose(false)
+        .dry_run(cli_args.as_ref().is_some_and(|cli_args| cli_args.dry_run))
+        .per_volume(cli_args.as_ref().is_some_and(|cli_args| cli_args.per_volume))
+        .incremental(cli_args.as_ref().is_some_and(|cli_args| cli_args.incremental))
+        .respect_robots(cli_args.as_ref().is_some_and(|cli_args| cli_args.respect_robots))
+        .merge_split_chapters(cli_args.as_ref().is_some_and(|cli_args| cli_args.merge_parts))
+        .reading_direction(if cli_args.as_ref().is_some_and(|cli_args| cli_args.rtl) { ReadingDirection::Rtl } else { ReadingDirection::Ltr })
+        .illustration_gallery(cli_args.as_ref().is_some_and(|cli_args| cli_args.gallery || cli_args.gallery_only))
+        .inline_illustrations(!cli_args.as_ref().is_some_and(|cli_args| cli_args.gallery_only))
+        .output_formats(cli_args.as_ref().map_or_else(|| vec![OutputFormat::Epub], |cli_args| cli_args.output_formats.clone()));
+    if let Some(base_url) = cli_args.as_ref().and_then(|cli_args| cli_args.base_url.as_deref()) {
+        builder = builder.base_url(base_url)?;
+    }
+    let crawler = builder.build();
+    
+    // 提供了命令行参数时以非交互模式批量爬取
+    if let Some(cli_args) = cli_args {
+        if cli_args.list_volumes {
+            return run_list_volumes(&crawler, &cli_args.novel_ids, cli_args.category, cli_args.json).await;
         }
-        
-        print!("\n是否继续爬取其他小说? (y/n): ");
-        io::stdout().flush()?;
-        let mut continue_choice = String::new();
-        io::stdin().read_line(&mut continue_choice)?;
-        if continue_choice.trim().to_lowercase() != "y" {
-            break;
+        for novel_id in cli_args.novel_ids {
+            println!("\n正在爬取 ID为 {} 的小说...", novel_id);
+            crawler.crawl_novel_in(novel_id, cli_args.category).await;
         }
+        return Ok(());
     }
     
-    println!("程序结束。");
-    Ok(())
-}
\ No newline at end of file
+    run_interactive(&crawler).await
+}
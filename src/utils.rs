@@ -1,13 +1,86 @@
-use anyhow::Result;
-use std::io;
+use crate::error::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use sha2::{Digest, Sha256};
+use chrono::NaiveDate;
+use reqwest::Url;
 
-pub fn get_user_input() -> Result<u32> {
-    println!("请输入小说ID: ");
-    let mut novel_id = String::new();
-    io::stdin().read_line(&mut novel_id)?;
-    let novel_id: u32 = novel_id.trim().parse()
-        .map_err(|_| anyhow::anyhow!("请输入有效的小说ID (数字)"))?;
-    
-    Ok(novel_id)
+/// 转义XML/XHTML中的特殊字符，并丢弃XML 1.0中不允许出现的控制字符
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {}
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
 
+/// 将连续的空白（包括换行、不间断空格）合并为单个空格，不去除首尾
+pub fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    collapsed
+}
+
+/// 合并连续空白并去除首尾空白，用于标题、作者名等单行文本
+pub fn normalize_whitespace(text: &str) -> String {
+    collapse_whitespace(text).trim().to_string()
+}
+
+/// 计算数据的SHA-256，返回小写十六进制字符串
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 解析站点上的日期，支持 YYYY-MM-DD 开头的ISO 8601时间（如time元素的datetime属性）和 dd/mm/yyyy
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    let date = text.trim().get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%d/%m/%Y"))
+        .ok()
+}
+
+/// 将相对URL补全为绝对URL
+///
+/// 协议相对（//host/...）的地址补全为https；根相对（/path）和文档相对（img/x.jpg、../x.jpg）的地址
+/// 按 `base_url` 解析，因此章节中的图片应传入章节页面的URL。已是绝对地址、为空或无法解析时原样返回。
+pub fn normalize_url(url: &str, base_url: &str) -> String {
+    let url = url.trim();
+    if url.starts_with("//") {
+        return format!("https:{}", url);
+    }
+    if url.is_empty() || Url::parse(url).is_ok() {
+        return url.to_string();
+    }
+    match Url::parse(base_url.trim()).and_then(|base| base.join(url)) {
+        Ok(absolute_url) => absolute_url.to_string(),
+        Err(_) if url.starts_with('/') => format!("{}{}", base_url.trim_end_matches('/'), url),
+        Err(_) => url.to_string(),
+    }
+}
+
+/// 从JSON文件加载章节标题覆盖映射（章节URL或章节ID -> 标题）
+pub fn load_title_overrides<P: AsRef<Path>>(path: P) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path)?;
+    let overrides: HashMap<String, String> = serde_json::from_str(&content)?;
+    Ok(overrides)
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+mod common;
+
+use common::fixture;
+use docln_fetch::crawler::blocked::{login_required, not_found};
+use reqwest::{StatusCode, Url};
+
+const CHAPTER_URL: &str = "https://docln.net/sang-tac/1234-thien-than-nha-ben/c1002-chuong-1";
+
+#[test]
+fn detects_login_page() {
+    let chapter_url = Url::parse(CHAPTER_URL).unwrap();
+    assert!(login_required(StatusCode::OK, &chapter_url, &fixture("login_page.html")));
+    assert!(login_required(StatusCode::UNAUTHORIZED, &chapter_url, ""));
+
+    let login_url = Url::parse("https://docln.net/login").unwrap();
+    assert!(login_required(StatusCode::OK, &login_url, ""));
+}
+
+#[test]
+fn chapter_page_does_not_require_login() {
+    let chapter_url = Url::parse(CHAPTER_URL).unwrap();
+    assert!(!login_required(StatusCode::OK, &chapter_url, &fixture("chapter_page.html")));
+    assert!(!login_required(StatusCode::NOT_FOUND, &chapter_url, "<html><body>Không tìm thấy</body></html>"));
+}
+
+#[test]
+fn detects_missing_novel_page() {
+    let body = fixture("not_found_page.html");
+    assert!(not_found(StatusCode::NOT_FOUND, &body));
+    // 以200返回的404页面按页面标记识别
+    assert!(not_found(StatusCode::OK, &body));
+}
+
+#[test]
+fn series_page_is_not_missing() {
+    assert!(!not_found(StatusCode::OK, &fixture("series_page.html")));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::fixture;
+use docln_fetch::crawler::ChapterProcessor;
+use docln_fetch::crawler::footnotes::apply_footnotes;
+
+#[test]
+fn extracts_chapter_paragraphs_without_ads() {
+    let paragraphs = ChapterProcessor::parse_chapter_paragraphs(&fixture("chapter_page.html"));
+
+    // 广告容器内的段落被丢弃，空段落保留
+    assert_eq!(paragraphs.len(), 7);
+    assert!(paragraphs.iter().all(|p_html| !p_html.contains("Quảng cáo")));
+    assert!(paragraphs.iter().all(|p_html| !p_html.contains("adsbygoogle")));
+    assert!(paragraphs[1].contains("<img"));
+    assert!(paragraphs[3].contains("Cậu sẽ bị cảm đấy."));
+}
+
+#[test]
+fn chapter_without_content_div_has_no_paragraphs() {
+    assert!(ChapterProcessor::parse_chapter_paragraphs("").is_empty());
+    assert!(ChapterProcessor::parse_chapter_paragraphs("<html><body><p>Không có nội dung</p></body></html>").is_empty());
+    assert!(ChapterProcessor::parse_chapter_paragraphs(r#"<div id="chapter-content"></div>"#).is_empty());
+}
+
+#[test]
+fn falls_back_to_alternate_content_containers() {
+    let paragraphs = ChapterProcessor::parse_chapter_paragraphs(&fixture("chapter_class_layout.html"));
+    assert_eq!(paragraphs.len(), 2);
+    assert!(paragraphs[1].contains("Cậu chưa ăn gì phải không?"));
+
+    // 空的 div#chapter-content 不会挡住后面的候选容器
+    let paragraphs = ChapterProcessor::parse_chapter_paragraphs(&fixture("chapter_reader_layout.html"));
+    assert_eq!(paragraphs.len(), 3);
+    assert!(paragraphs[0].contains("Amane đánh rơi chìa khóa."));
+}
+
+#[test]
+fn converts_note_markers_to_footnotes() {
+    let html = fixture("chapter_page.html");
+    let paragraphs = ChapterProcessor::parse_chapter_paragraphs(&html);
+    let (paragraphs, footnotes) = apply_footnotes(&html, paragraphs);
+
+    assert_eq!(footnotes.len(), 1);
+    assert_eq!(footnotes[0].number, 1);
+    assert_eq!(footnotes[0].text, "Xích đu ở công viên gần nhà Amane.");
+    assert!(paragraphs[2].contains(r##"<a epub:type="noteref" href="#note-1" id="noteref-1">[1]</a>"##));
+    assert!(!paragraphs[2].contains("note-icon"));
+}
+
+#[test]
+fn builds_chapter_xhtml() {
+    let html = fixture("chapter_page.html");
+    let paragraphs = ChapterProcessor::parse_chapter_paragraphs(&html);
+    let (paragraphs, footnotes) = apply_footnotes(&html, paragraphs);
+    let xhtml = ChapterProcessor::build_chapter_xhtml("Chương 1", Some("03/02/2023"), &paragraphs, &footnotes, "vi");
+
+    assert!(xhtml.starts_with("<?xml"));
+    assert!(xhtml.contains(r#"xml:lang="vi""#));
+    assert!(xhtml.contains("<title>Chương 1</title>"));
+    assert!(xhtml.contains(r#"<p class="chapter-date">03/02/2023</p>"#));
+    assert!(xhtml.contains(r#"<aside epub:type="footnote" id="note-1">"#));
+    // 规范化后的img为自闭合标签
+    assert!(xhtml.contains(r#"c1002-01.jpg"/>"#));
+}
+
+#[test]
+fn collapses_whitespace_but_keeps_paragraphs() {
+    let paragraphs = vec![
+        "<p>Trời\n        đổ\u{a0}\u{a0}mưa  <em>từ\n chiều</em>.</p>".to_string(),
+        "<p>Hết chương.</p>".to_string(),
+    ];
+    let xhtml = ChapterProcessor::build_chapter_xhtml("Chương 1", None, &paragraphs, &[], "vi");
+
+    assert!(xhtml.contains("<p>Trời đổ mưa <em>từ chiều</em>.</p>\n<p>Hết chương.</p>"));
+}
+
+#[test]
+fn repairs_broken_markup_into_well_formed_xhtml() {
+    let paragraphs = ChapterProcessor::parse_chapter_paragraphs(&fixture("broken_chapter_page.html"));
+    let xhtml = ChapterProcessor::build_chapter_xhtml("Chương 4 <sửa>", None, &paragraphs, &[], "vi");
+
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    let document = roxmltree::Document::parse_with_options(&xhtml, options).expect("chapter XHTML should be well-formed");
+    let text: String = document.descendants().filter(|node| node.is_text()).map(|node| node.text().unwrap()).collect();
+    assert!(text.contains("Đoạn mở đầu chưa đóng thẻ"));
+    assert!(text.contains("Đoạn có thuộc tính lạ và thẻ thừa."));
+    assert!(text.contains("lồng sai"));
+    assert!(text.contains("Ký tự điều khiển: xong"));
+    assert!(!xhtml.contains("@click"));
+    assert!(xhtml.contains(r#"data-x="a&amp;b""#));
+}
+
+#[test]
+fn detects_chapter_opening_with_image() {
+    let paragraphs = |items: &[&str]| items.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+
+    assert!(ChapterProcessor::starts_with_image(&paragraphs(&[
+        "<p>&nbsp;</p>",
+        r#"<p><img data-src="https://i.docln.net/banner.jpg" src="loading.gif"></p>"#,
+        "<p>Chương mở đầu.</p>",
+    ])));
+    assert!(!ChapterProcessor::starts_with_image(&paragraphs(&[
+        r#"<p>Trước tiên, <img src="https://i.docln.net/inline.jpg"></p>"#,
+    ])));
+    assert!(!ChapterProcessor::starts_with_image(&paragraphs(&["<p>Không có hình.</p>"])));
+}
@@ -0,0 +1,162 @@
+// 各测试文件只用到其中一部分辅助函数
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 读取 tests/fixtures 下保存的页面HTML
+pub fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("无法读取测试页面 {}: {}", path.display(), e))
+}
+
+/// 1x1的PNG图片
+pub fn png_bytes() -> Vec<u8> {
+    const PNG_HEX: &str = "89504E470D0A1A0A0000000D49484452000000010000000108060000001F15C4890000000A49444154789C63000100000500010D0A2DB40000000049454E44AE426082";
+    (0..PNG_HEX.len()).step_by(2).map(|i| u8::from_str_radix(&PNG_HEX[i..i + 2], 16).unwrap()).collect()
+}
+
+/// 模拟服务器对一个请求的响应
+#[derive(Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Vec<u8>,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self { status, content_type: content_type.to_string(), body: body.into(), delay: Duration::ZERO }
+    }
+
+    pub fn html(body: impl Into<String>) -> Self {
+        Self::new(200, "text/html; charset=utf-8", body.into())
+    }
+
+    pub fn png() -> Self {
+        Self::new(200, "image/png", png_bytes())
+    }
+
+    pub fn not_found() -> Self {
+        Self::new(404, "text/html; charset=utf-8", fixture("not_found_page.html"))
+    }
+
+    /// 延迟一段时间后再响应，用于观察同时处理中的请求数
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// 模拟服务器收到的请求
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub path: String,
+    pub user_agent: String,
+}
+
+/// 在本机随机端口上运行的HTTP服务器，按请求路径调用handler生成响应，并记录收到的请求
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+impl MockServer {
+    pub async fn start(handler: impl Fn(&str) -> MockResponse + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let handler = Arc::new(handler);
+
+        let server = Self { url, requests: requests.clone(), max_in_flight: max_in_flight.clone() };
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                let handler = handler.clone();
+                let requests = requests.clone();
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut socket).await else {
+                        return;
+                    };
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    requests.lock().unwrap().push(request.clone());
+
+                    let response = handler(&request.path);
+                    tokio::time::sleep(response.delay).await;
+                    // 在发出响应前结束计数，客户端收到响应并释放许可之后的请求不会与这一个重叠计数
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    let head = format!(
+                        "HTTP/1.1 {} Mock\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        response.status,
+                        response.content_type,
+                        response.body.len()
+                    );
+                    let _ = socket.write_all(head.as_bytes()).await;
+                    let _ = socket.write_all(&response.body).await;
+                    let _ = socket.shutdown().await;
+                });
+            }
+        });
+        server
+    }
+
+    /// 按到达顺序返回收到的请求
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// 同时处理中的请求数的最大值
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+/// 读取请求行和请求头，返回路径和User-Agent
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<MockRequest> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let head = String::from_utf8_lossy(&buffer);
+    let mut lines = head.lines();
+    let path = lines.next()?.split_whitespace().nth(1)?.to_string();
+    let user_agent = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("user-agent"))
+        .map(|(_, value)| value.trim().to_string())
+        .unwrap_or_default();
+    Some(MockRequest { path, user_agent })
+}
+
+/// 模拟小说站点：小说页面和章节页面来自测试页面（图片地址改为站内的根相对地址），图片为1x1的PNG
+pub fn novel_site(path: &str) -> MockResponse {
+    let last_segment = path.split('?').next().unwrap_or(path).rsplit('/').next().unwrap_or("");
+    if path.starts_with("/lightnovel/") {
+        MockResponse::png()
+    } else if last_segment.starts_with('c') && last_segment[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        MockResponse::html(fixture("chapter_page.html").replace("https://i.docln.net", ""))
+    } else if path.starts_with("/sang-tac/1234") {
+        MockResponse::html(fixture("series_page.html").replace("https://i.docln.net", ""))
+    } else {
+        MockResponse::not_found()
+    }
+}
@@ -0,0 +1,53 @@
+use docln_fetch::epub::EpubCompressor;
+use std::fs;
+use std::path::Path;
+
+/// 按给定顺序写入同一组文件
+fn write_tree(epub_dir: &Path, names: &[&str]) {
+    for name in names {
+        let path = epub_dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("<p>{}</p>", name)).unwrap();
+    }
+}
+
+#[test]
+fn compressing_same_tree_is_byte_identical() {
+    let root = std::env::temp_dir().join(format!("docln_fetch_test_zip_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let epub_dir = root.join("epub_1");
+    let names = ["mimetype", "OEBPS/content.opf", "OEBPS/text/volume_001/chapter_001.xhtml", "OEBPS/text/title.xhtml", "META-INF/container.xml"];
+
+    write_tree(&epub_dir, &names);
+    EpubCompressor::new().keep_staging(true).compress_epub(&epub_dir).unwrap();
+    let first = fs::read(root.join("docln_1.epub")).unwrap();
+
+    // 以相反的顺序重新创建目录，输出不应受文件系统顺序影响
+    fs::remove_dir_all(&epub_dir).unwrap();
+    write_tree(&epub_dir, &names.iter().rev().copied().collect::<Vec<_>>());
+    EpubCompressor::new().compress_epub(&epub_dir).unwrap();
+    let second = fs::read(root.join("docln_1.epub")).unwrap();
+
+    assert_eq!(first, second);
+    let archive = zip::ZipArchive::new(fs::File::open(root.join("docln_1.epub")).unwrap()).unwrap();
+    assert_eq!(archive.name_for_index(0), Some("mimetype"));
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn stores_images_and_deflates_text() {
+    let root = std::env::temp_dir().join(format!("docln_fetch_test_zip_methods_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let epub_dir = root.join("epub_2");
+    write_tree(&epub_dir, &["mimetype", "OEBPS/content.opf", "OEBPS/images/cover.JPG", "OEBPS/images/v001_c001_i001.png"]);
+
+    EpubCompressor::new().compression_level(1).compress_epub(&epub_dir).unwrap();
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(root.join("docln_2.epub")).unwrap()).unwrap();
+    let method = |archive: &mut zip::ZipArchive<fs::File>, name: &str| archive.by_name(name).unwrap().compression();
+    assert_eq!(method(&mut archive, "mimetype"), zip::CompressionMethod::Stored);
+    assert_eq!(method(&mut archive, "OEBPS/images/cover.JPG"), zip::CompressionMethod::Stored);
+    assert_eq!(method(&mut archive, "OEBPS/images/v001_c001_i001.png"), zip::CompressionMethod::Stored);
+    assert_eq!(method(&mut archive, "OEBPS/content.opf"), zip::CompressionMethod::Deflated);
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,513 @@
+mod common;
+
+use common::{fixture, novel_site, MockResponse, MockServer};
+use docln_fetch::{DoclnCrawler, DoclnError, EpubGenerator, Volume, VolumeSummary};
+use docln_fetch::crawler::{ChapterProcessor, DEFAULT_BASE_URL, KNOWN_MIRRORS, NovelParser, RequestLimiter, UserAgentRotator};
+use scraper::Html;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn defaults_to_primary_site() {
+    assert_eq!(DoclnCrawler::new().base_url(), DEFAULT_BASE_URL);
+    assert_eq!(KNOWN_MIRRORS[0], DEFAULT_BASE_URL);
+}
+
+#[test]
+fn accepts_mirror_base_url() {
+    let crawler = DoclnCrawler::builder().base_url("https://ln.hako.vn/").unwrap().build();
+    assert_eq!(crawler.base_url(), "https://ln.hako.vn");
+}
+
+#[test]
+fn rejects_invalid_base_url() {
+    assert!(DoclnCrawler::builder().base_url("ln.hako.vn").is_err());
+    assert!(DoclnCrawler::builder().base_url("ftp://ln.hako.vn").is_err());
+}
+
+#[test]
+fn cloned_builder_configures_independent_crawlers() {
+    let builder = DoclnCrawler::builder().request_delay(Duration::ZERO);
+    let mirror = builder.clone().base_url("https://docln.sbs").unwrap().build();
+    let primary = builder.build();
+
+    assert_eq!(primary.base_url(), DEFAULT_BASE_URL);
+    assert_eq!(mirror.base_url(), "https://docln.sbs");
+    assert_eq!(mirror.request_delay_range(), (Duration::ZERO, Duration::ZERO));
+    assert_eq!(mirror.clone().base_url(), "https://docln.sbs");
+}
+
+#[tokio::test]
+async fn reports_error_when_oebps_dir_cannot_be_derived() {
+    let processor = ChapterProcessor::new(
+        reqwest::Client::new(),
+        DEFAULT_BASE_URL.to_string(),
+        RequestLimiter::default(),
+        UserAgentRotator::default(),
+        Duration::ZERO,
+    );
+
+    let result = processor
+        .fetch_chapter_content("https://docln.net/truyen/1234/c1", 0, 0, "Tập 1", "Chương 1", None, Path::new("/"), false)
+        .await;
+
+    assert!(matches!(result, Err(DoclnError::InvalidInput(_))));
+}
+
+#[test]
+fn summarizes_volumes_in_listing_order() {
+    let html = fixture("series_page.html");
+    let document = Html::parse_document(&html);
+    let mut epub = NovelParser.parse_novel_info(&html, "https://docln.net/sang-tac/1234", 1234).unwrap();
+    for (title, volume_id) in NovelParser.parse_volume_info(&document) {
+        let chapters = NovelParser.parse_volume_chapters(&document, &volume_id);
+        epub.volumes.push(Volume::builder().title(title).volume_id(volume_id).chapters(chapters).build());
+    }
+
+    let volumes = VolumeSummary::list(&epub);
+
+    assert_eq!(volumes.len(), 2);
+    assert_eq!(volumes[1].index, 2);
+    assert_eq!(volumes[1].volume_id, "#volume_102");
+    assert_eq!(volumes[0].to_tsv_line(), format!("1\t#volume_101\t{}\tTập 01", volumes[0].chapter_count));
+}
+
+#[tokio::test]
+async fn stores_absolute_chapter_urls() {
+    let crawler = DoclnCrawler::builder().base_url("https://ln.hako.vn/").unwrap().build();
+
+    let epub = crawler.parse_novel_listing(&fixture("oneshot_page.html"), "https://ln.hako.vn/sang-tac/5678", 5678).await.unwrap();
+
+    let urls: Vec<&str> = epub.volumes.iter().flat_map(|volume| &volume.chapters).map(|chapter| chapter.url.as_str()).collect();
+    assert_eq!(urls.len(), 2);
+    assert!(urls.iter().all(|url| url.starts_with("https://ln.hako.vn/sang-tac/5678")));
+}
+
+#[tokio::test]
+async fn merges_paginated_chapter_lists_in_order() {
+    let server = MockServer::start(|path| match path {
+        "/sang-tac/1234-thien-than-nha-ben?page=2" => MockResponse::html(fixture("series_paged_page_2.html")),
+        _ => MockResponse::not_found(),
+    })
+    .await;
+    let crawler = DoclnCrawler::builder().base_url(&server.url).unwrap().request_delay(Duration::ZERO).build();
+    let series_url = format!("{}/sang-tac/1234-thien-than-nha-ben", server.url);
+
+    let epub = crawler.parse_novel_listing(&fixture("series_paged_page.html"), &series_url, 1234).await.unwrap();
+
+    // 第二页的章节接在第一页之后，两页都出现的章节只保留一次
+    assert_eq!(epub.volumes.len(), 1);
+    let chapters: Vec<(&str, String)> = epub.volumes[0]
+        .chapters
+        .iter()
+        .map(|chapter| (chapter.title.as_str(), chapter.url.trim_start_matches(&server.url).to_string()))
+        .collect();
+    assert_eq!(
+        chapters,
+        [
+            ("Chương 1", "/sang-tac/1234-thien-than-nha-ben/c2001-chuong".to_string()),
+            ("Chương 2", "/sang-tac/1234-thien-than-nha-ben/c2002-chuong".to_string()),
+            ("Chương 3", "/sang-tac/1234-thien-than-nha-ben/c2003-chuong".to_string()),
+            ("Chương 4", "/sang-tac/1234-thien-than-nha-ben/c2004-chuong".to_string()),
+        ]
+    );
+    let requested: Vec<String> = server.requests().into_iter().map(|request| request.path).collect();
+    assert_eq!(requested, ["/sang-tac/1234-thien-than-nha-ben?page=2"]);
+}
+
+#[tokio::test]
+async fn title_overrides_match_absolute_and_relative_urls() {
+    let title_overrides = HashMap::from([
+        ("https://docln.net/sang-tac/1234-thien-than-nha-ben/c1003-chuong-2".to_string(), "Chương 2: Bữa tối".to_string()),
+        ("/sang-tac/1234-thien-than-nha-ben/c1004-chuong-1".to_string(), "Chương 1: Mùa xuân".to_string()),
+    ]);
+    let crawler = DoclnCrawler::builder().title_overrides(title_overrides).build();
+
+    let epub = crawler.parse_novel_listing(&fixture("series_page.html"), "https://docln.net/sang-tac/1234", 1234).await.unwrap();
+
+    assert_eq!(epub.volumes[0].chapters[2].title, "Chương 2: Bữa tối");
+    assert_eq!(epub.volumes[1].chapters[0].title, "Chương 1: Mùa xuân");
+}
+
+#[tokio::test]
+async fn concurrent_volumes_keep_listing_order() {
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_volume_concurrency_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    // 封面换成默认的nocover图片，章节全部预先写好，续传模式下不发出任何请求
+    let html = fixture("series_page.html").replace("/covers/", "/covers/nocover-");
+    let crawler = DoclnCrawler::builder()
+        .base_url("http://127.0.0.1:9")
+        .unwrap()
+        .output_dir(&output_dir)
+        .resume(true)
+        .volume_concurrency(2)
+        .request_delay(Duration::ZERO)
+        .build();
+    let listing = crawler.parse_novel_listing(&html, "http://127.0.0.1:9/sang-tac/1234", 1234).await.unwrap();
+    let oebps_dir = crawler.epub_dir(1234).join("OEBPS");
+    for (volume_index, volume) in listing.volumes.iter().enumerate() {
+        for chapter_index in 0..volume.chapters.len() {
+            let path = oebps_dir.join(format!("text/volume_{:03}/chapter_{:03}.xhtml", volume_index + 1, chapter_index + 1));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "<html/>").unwrap();
+        }
+    }
+
+    let epub = crawler.parse_novel_info(&html, "http://127.0.0.1:9/sang-tac/1234", 1234).await.unwrap();
+
+    let titles: Vec<&str> = epub.volumes.iter().map(|volume| volume.title.as_str()).collect();
+    assert_eq!(titles, listing.volumes.iter().map(|volume| volume.title.as_str()).collect::<Vec<_>>());
+    for (volume_index, volume) in epub.volumes.iter().enumerate() {
+        let prefix = format!("text/volume_{:03}/", volume_index + 1);
+        assert!(volume.chapters.iter().all(|chapter| chapter.xhtml_path.as_deref().is_some_and(|path| path.starts_with(&prefix))));
+    }
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[tokio::test]
+async fn title_overrides_reach_chapter_heading_and_toc() {
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_title_overrides_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let title_overrides = HashMap::from([
+        ("c1002-chuong-1".to_string(), "Chương 1: Cây dù".to_string()),
+        ("https://docln.net/sang-tac/1234-thien-than-nha-ben/c1005-chuong-2".to_string(), "Chương 2: Lời hẹn".to_string()),
+        ("c9999-khong-ton-tai".to_string(), "Không có".to_string()),
+    ]);
+    let crawler = DoclnCrawler::builder().title_overrides(title_overrides.clone()).output_dir(&output_dir).build();
+    let mut epub = crawler.parse_novel_listing(&fixture("series_page.html"), "https://docln.net/sang-tac/1234", 1234).await.unwrap();
+
+    // 未命中的条目会被报告
+    let mut matched = HashSet::new();
+    for volume in &mut epub.clone().volumes {
+        NovelParser.apply_title_overrides(&mut volume.chapters, &title_overrides, &mut matched);
+    }
+    assert_eq!(NovelParser.unmatched_title_overrides(&title_overrides, &matched), vec!["c9999-khong-ton-tai"]);
+
+    // 按处理器写入章节的方式生成XHTML，再打包EPUB
+    let epub_dir = crawler.epub_dir(1234);
+    for (volume_index, volume) in epub.volumes.iter_mut().enumerate() {
+        for (chapter_index, chapter) in volume.chapters.iter_mut().enumerate() {
+            let xhtml_path = format!("text/volume_{:03}/chapter_{:03}.xhtml", volume_index + 1, chapter_index + 1);
+            let path = epub_dir.join("OEBPS").join(&xhtml_path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, ChapterProcessor::build_chapter_xhtml(&chapter.title, None, &[], &[], "vi")).unwrap();
+            chapter.xhtml_path = Some(xhtml_path);
+        }
+    }
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(output_dir.join("docln_1234.epub")).unwrap()).unwrap();
+    let mut read_entry = |name: &str| {
+        let mut content = String::new();
+        archive.by_name(name).unwrap().read_to_string(&mut content).unwrap();
+        content
+    };
+    let chapter_xhtml = read_entry("OEBPS/text/volume_001/chapter_002.xhtml");
+    assert!(chapter_xhtml.contains("<title>Chương 1: Cây dù</title>"));
+    assert!(chapter_xhtml.contains("<h1>Chương 1: Cây dù</h1>"));
+    let chapter_xhtml = read_entry("OEBPS/text/volume_002/chapter_002.xhtml");
+    assert!(chapter_xhtml.contains("<h1>Chương 2: Lời hẹn</h1>"));
+    let toc_ncx = read_entry("OEBPS/toc.ncx");
+    assert!(toc_ncx.contains("<text>Chương 1: Cây dù</text>"));
+    assert!(toc_ncx.contains("<text>Chương 2: Lời hẹn</text>"));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+/// 读取EPUB中的全部条目 (路径, 内容)，保持ZIP中的顺序
+fn read_epub_entries(path: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut archive = zip::ZipArchive::new(fs::File::open(path).unwrap()).unwrap();
+    (0..archive.len())
+        .map(|i| {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            (entry.name().to_string(), content)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn streaming_crawl_matches_file_based_epub() {
+    let server = MockServer::start(novel_site).await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_streaming_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let builder = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .request_delay(Duration::ZERO)
+        .verbose(false);
+
+    let file_dir = output_dir.join("file");
+    builder.clone().output_dir(&file_dir).build().crawl_to_epub(1234).await.unwrap();
+    // 流式模式下即使要求保留暂存文件夹，也不会写入工作目录
+    let stream_dir = output_dir.join("stream");
+    let crawler = builder.output_dir(&stream_dir).keep_staging(true).streaming(true).build();
+    crawler.crawl_to_epub(1234).await.unwrap();
+    assert!(!crawler.epub_dir(1234).exists());
+    assert!(!stream_dir.join("docln_1234.epub.part").exists());
+
+    let file_entries = read_epub_entries(&file_dir.join("docln_1234.epub"));
+    let stream_entries = read_epub_entries(&stream_dir.join("docln_1234.epub"));
+    assert_eq!(stream_entries[0], ("mimetype".to_string(), b"application/epub+zip".to_vec()));
+    let mut file_entries_sorted = file_entries.clone();
+    let mut stream_entries_sorted = stream_entries.clone();
+    file_entries_sorted.sort();
+    stream_entries_sorted.sort();
+    let names = |entries: &[(String, Vec<u8>)]| entries.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(&stream_entries_sorted), names(&file_entries_sorted));
+    assert!(names(&stream_entries_sorted).contains(&"OEBPS/images/volume_001/chapter_001/001.png".to_string()));
+    for ((name, file_content), (_, stream_content)) in file_entries_sorted.iter().zip(&stream_entries_sorted) {
+        assert_eq!(file_content, stream_content, "{} 的内容不同", name);
+    }
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[tokio::test]
+async fn gallery_only_moves_illustrations_out_of_chapters() {
+    let server = MockServer::start(novel_site).await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_gallery_only_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let crawler = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .request_delay(Duration::ZERO)
+        .verbose(false)
+        .output_dir(&output_dir)
+        .inline_illustrations(false)
+        .build();
+    crawler.crawl_to_epub(1234).await.unwrap();
+
+    let entries: HashMap<String, Vec<u8>> = read_epub_entries(&output_dir.join("docln_1234.epub")).into_iter().collect();
+    let text = |name: &str| String::from_utf8(entries[name].clone()).unwrap();
+    // 插图已下载，但只出现在插图集中；只含插图的段落随之移除
+    assert!(entries.contains_key("OEBPS/images/volume_001/chapter_001/001.png"));
+    let chapter = text("OEBPS/text/volume_001/chapter_001.xhtml");
+    assert!(!chapter.contains("<img"), "{}", chapter);
+    assert!(!chapter.contains("id=\"2\""), "{}", chapter);
+    let gallery = text("OEBPS/text/volume_001/gallery.xhtml");
+    assert!(gallery.contains("src=\"../../images/volume_001/chapter_001/001.png\""), "{}", gallery);
+    assert!(text("OEBPS/content.opf").contains("href=\"text/volume_001/gallery.xhtml\""));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[tokio::test]
+async fn downloads_document_relative_illustrations() {
+    // 章节页面中的插图使用相对章节页面的地址
+    let server = MockServer::start(|path| {
+        let response = novel_site(path);
+        if path.starts_with("/sang-tac/") && path.contains("/c1") {
+            let html = String::from_utf8(response.body).unwrap().replace("/lightnovel/illusts/", "../../lightnovel/illusts/");
+            MockResponse::html(html)
+        } else {
+            response
+        }
+    })
+    .await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_relative_images_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let crawler = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .request_delay(Duration::ZERO)
+        .verbose(false)
+        .output_dir(&output_dir)
+        .build();
+    crawler.crawl_to_epub(1234).await.unwrap();
+
+    assert!(server.requests().iter().any(|request| request.path == "/lightnovel/illusts/u1234/c1002-01.jpg"));
+    let entries: HashMap<String, Vec<u8>> = read_epub_entries(&output_dir.join("docln_1234.epub")).into_iter().collect();
+    assert!(entries.contains_key("OEBPS/images/volume_001/chapter_001/001.png"));
+    let chapter = String::from_utf8(entries["OEBPS/text/volume_001/chapter_001.xhtml"].clone()).unwrap();
+    assert!(chapter.contains("src=\"../../images/volume_001/chapter_001/001.png\""), "{}", chapter);
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[tokio::test]
+async fn volumes_with_same_title_keep_their_own_covers() {
+    // 两卷同名但封面不同
+    let server = MockServer::start(|path| {
+        if path.starts_with("/sang-tac/1234") && !path.contains("/c1") {
+            MockResponse::html(fixture("series_page.html").replace("https://i.docln.net", "").replace("Tập 02", "Tập 01"))
+        } else {
+            novel_site(path)
+        }
+    })
+    .await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_same_title_covers_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let crawler = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .request_delay(Duration::ZERO)
+        .verbose(false)
+        .output_dir(&output_dir)
+        .keep_staging(true)
+        .build();
+    let epub = crawler.crawl_to_epub(1234).await.unwrap();
+
+    let requested: Vec<String> = server.requests().into_iter().map(|request| request.path).collect();
+    assert!(requested.iter().any(|path| path == "/lightnovel/covers/v101-cover.jpg"));
+    assert!(requested.iter().any(|path| path == "/lightnovel/covers/v102-cover.jpg"));
+    let covers: Vec<String> = epub.volumes.iter().map(|volume| volume.cover_image_path.clone().unwrap()).collect();
+    assert_eq!(covers, ["images/volume_001_Tập_01.png", "images/volume_002_Tập_01.png"]);
+    for cover in &covers {
+        assert!(crawler.epub_dir(1234).join("OEBPS").join(cover).is_file());
+    }
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[tokio::test]
+async fn chapter_links_become_absolute_or_plain_text() {
+    // 章节正文中有根相对和文档相对的链接
+    let server = MockServer::start(|path| {
+        let response = novel_site(path);
+        if path.starts_with("/sang-tac/") && path.contains("/c1") {
+            let html = String::from_utf8(response.body).unwrap().replace(
+                "<a href=\"/sang-tac/1234-thien-than-nha-ben\">trang truyện</a>",
+                "<a href=\"/sang-tac/1234-thien-than-nha-ben\">trang truyện</a> và <a href=\"c1003-chuong-2\">chương sau</a>",
+            );
+            MockResponse::html(html)
+        } else {
+            response
+        }
+    })
+    .await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_chapter_links_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let builder = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .request_delay(Duration::ZERO)
+        .verbose(false);
+
+    let linked_dir = output_dir.join("linked");
+    builder.clone().output_dir(&linked_dir).build().crawl_to_epub(1234).await.unwrap();
+    let entries: HashMap<String, Vec<u8>> = read_epub_entries(&linked_dir.join("docln_1234.epub")).into_iter().collect();
+    let chapter = String::from_utf8(entries["OEBPS/text/volume_001/chapter_001.xhtml"].clone()).unwrap();
+    assert!(chapter.contains(&format!("<a href=\"{}/sang-tac/1234-thien-than-nha-ben\">trang truyện</a>", server.url)), "{}", chapter);
+    assert!(chapter.contains(&format!("<a href=\"{}/sang-tac/1234-thien-than-nha-ben/c1003-chuong-2\">chương sau</a>", server.url)), "{}", chapter);
+
+    // 去除链接时只保留文字
+    let stripped_dir = output_dir.join("stripped");
+    builder.output_dir(&stripped_dir).strip_links(true).build().crawl_to_epub(1234).await.unwrap();
+    let entries: HashMap<String, Vec<u8>> = read_epub_entries(&stripped_dir.join("docln_1234.epub")).into_iter().collect();
+    let chapter = String::from_utf8(entries["OEBPS/text/volume_001/chapter_001.xhtml"].clone()).unwrap();
+    assert!(chapter.contains("đọc thêm tại trang truyện và chương sau."), "{}", chapter);
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[tokio::test]
+async fn streaming_rejects_file_based_options() {
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_streaming_options_{}", std::process::id()));
+    let crawler = DoclnCrawler::builder().output_dir(&output_dir).streaming(true).per_volume(true).build();
+    assert!(matches!(crawler.crawl_to_epub(1234).await, Err(DoclnError::InvalidInput(_))));
+    assert!(!output_dir.exists());
+}
+
+#[tokio::test]
+async fn dropping_crawl_future_stops_volume_tasks() {
+    // 章节响应较慢，Future被丢弃时各卷的任务都还在处理中
+    let server = MockServer::start(|path| {
+        let response = novel_site(path);
+        if path.contains("/c1") { response.delay(Duration::from_millis(200)) } else { response }
+    })
+    .await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_drop_crawl_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let crawler = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .output_dir(&output_dir)
+        .volume_concurrency(2)
+        .chapter_concurrency(1)
+        .request_delay(Duration::ZERO)
+        .verbose(false)
+        .build();
+
+    assert!(tokio::time::timeout(Duration::from_millis(100), crawler.crawl_to_epub(1234)).await.is_err());
+    let requests_at_drop = server.requests().len();
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert_eq!(server.requests().len(), requests_at_drop);
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[tokio::test]
+async fn closed_event_receiver_starts_no_volume() {
+    let server = MockServer::start(novel_site).await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_closed_events_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let crawler = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .output_dir(&output_dir)
+        .request_delay(Duration::ZERO)
+        .verbose(false)
+        .build();
+
+    let (events, crawl) = crawler.crawl_stream(1234);
+    drop(events);
+    assert!(matches!(crawl.await, Err(DoclnError::Cancelled)));
+    assert!(server.requests().iter().all(|request| !request.path.contains("/c1")));
+    let _ = fs::remove_dir_all(output_dir);
+}
+
+#[tokio::test]
+async fn health_check_reports_reachable_site_and_markup() {
+    let server = MockServer::start(|_| MockResponse::html(fixture("series_page.html"))).await;
+    let crawler = DoclnCrawler::builder().base_url(&server.url).unwrap().build();
+
+    let health = crawler.health_check().await.unwrap();
+    assert!(health.reachable);
+    assert!(health.markup_ok);
+    assert_eq!(health.status_code, Some(200));
+    assert_eq!(server.requests()[0].path, "/");
+
+    // 页面改版后找不到小说链接：可访问但页面结构不符合预期
+    let server = MockServer::start(|_| MockResponse::html("<html><body><p>Trang chủ mới</p></body></html>")).await;
+    let crawler = DoclnCrawler::builder().base_url(&server.url).unwrap().build();
+    let health = crawler.health_check().await.unwrap();
+    assert!(health.reachable);
+    assert!(!health.markup_ok);
+}
+
+#[tokio::test]
+async fn health_check_reports_blocked_and_unreachable_site() {
+    let server = MockServer::start(|_| MockResponse::new(403, "text/html", "<html><title>Just a moment...</title></html>")).await;
+    let crawler = DoclnCrawler::builder().base_url(&server.url).unwrap().build();
+
+    let health = crawler.health_check().await.unwrap();
+    assert!(!health.reachable);
+    assert!(!health.markup_ok);
+    assert_eq!(health.status_code, Some(403));
+
+    // 没有服务监听的端口：连接失败，没有状态码
+    let crawler = DoclnCrawler::builder().base_url("http://127.0.0.1:9").unwrap().build();
+    let health = crawler.health_check().await.unwrap();
+    assert!(!health.reachable);
+    assert!(!health.markup_ok);
+    assert_eq!(health.status_code, None);
+}
+
+#[tokio::test]
+async fn rotates_user_agents_across_requests() {
+    let server = MockServer::start(|_| MockResponse::html(fixture("series_page.html"))).await;
+    let agents = vec!["docln-test/1".to_string(), "docln-test/2".to_string(), "docln-test/3".to_string()];
+    let crawler = DoclnCrawler::builder().base_url(&server.url).unwrap().user_agents(agents).build();
+
+    for _ in 0..4 {
+        crawler.health_check().await.unwrap();
+    }
+
+    let user_agents: Vec<String> = server.requests().into_iter().map(|request| request.user_agent).collect();
+    assert_eq!(user_agents, ["docln-test/1", "docln-test/2", "docln-test/3", "docln-test/1"]);
+
+    // 只有一个UA时每个请求都使用它
+    let server = MockServer::start(|_| MockResponse::html(fixture("series_page.html"))).await;
+    let crawler = DoclnCrawler::builder().base_url(&server.url).unwrap().user_agent("docln-test/only").build();
+    crawler.health_check().await.unwrap();
+    crawler.health_check().await.unwrap();
+    assert!(server.requests().iter().all(|request| request.user_agent == "docln-test/only"));
+}
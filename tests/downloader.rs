@@ -0,0 +1,108 @@
+mod common;
+
+use common::{fixture, png_bytes, MockResponse, MockServer};
+use docln_fetch::DoclnError;
+use docln_fetch::crawler::{ImageDownloader, RequestLimiter, UserAgentRotator};
+use docln_fetch::crawler::downloader::detect_image_extension;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn downloader(server: &MockServer) -> ImageDownloader {
+    ImageDownloader::new(reqwest::Client::new(), RequestLimiter::default(), UserAgentRotator::default())
+        .base_url(&server.url)
+        .verbose(false)
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("docln_fetch_test_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 只有文件头的1x1 GIF
+fn gif_bytes() -> Vec<u8> {
+    b"GIF89a\x01\x00\x01\x00\x00\x00\x00;".to_vec()
+}
+
+#[tokio::test]
+async fn rejects_error_responses_without_saving_them() {
+    let server = MockServer::start(|path| match path {
+        "/html.jpg" => MockResponse::html(fixture("not_found_page.html")),
+        "/empty.jpg" => MockResponse::new(200, "image/jpeg", Vec::new()),
+        _ => MockResponse::not_found(),
+    })
+    .await;
+    let dir = temp_dir("image_errors");
+    let downloader = downloader(&server);
+
+    // 返回200的HTML错误页面、空内容和404都不会保存为图片，也不会重试
+    assert!(matches!(downloader.download_image("/html.jpg", &dir, "html", "").await, Err(DoclnError::Image(_))));
+    assert!(matches!(downloader.download_image("/empty.jpg", &dir, "empty", "").await, Err(DoclnError::Image(_))));
+    assert!(matches!(downloader.download_image("/missing.jpg", &dir, "missing", "").await, Err(DoclnError::Network(_))));
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_the_image_arrives() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let server_attempts = attempts.clone();
+    let server = MockServer::start(move |path| match path {
+        // 第一次请求返回503，之后正常返回图片
+        "/flaky.png" if server_attempts.fetch_add(1, Ordering::SeqCst) == 0 => MockResponse::new(503, "text/html", "busy"),
+        "/flaky.png" => MockResponse::png(),
+        _ => MockResponse::new(503, "text/html", "busy"),
+    })
+    .await;
+    let dir = temp_dir("image_retries");
+    let downloader = downloader(&server);
+
+    let filename = downloader.download_image("/flaky.png", &dir, "001", "").await.unwrap();
+    assert_eq!(filename, "001.png");
+    assert_eq!(fs::read(dir.join(&filename)).unwrap(), png_bytes());
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    // 一直失败时在重试次数用完后放弃：首次请求加两次重试
+    assert!(downloader.download_image("/down.png", &dir, "002", "").await.is_err());
+    assert_eq!(server.requests().iter().filter(|request| request.path == "/down.png").count(), 3);
+    assert!(!dir.join("002.png").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn names_images_by_content_not_url() {
+    let server = MockServer::start(|path| match path {
+        // 地址和Content-Type都说是JPEG，实际内容是PNG
+        "/lightnovel/illusts/a.jpg?v=2" => MockResponse::new(200, "image/jpeg", png_bytes()),
+        // 地址是PNG，Content-Type没有给出格式，实际内容是GIF
+        "/lightnovel/illusts/b.png" => MockResponse::new(200, "application/octet-stream", gif_bytes()),
+        _ => MockResponse::not_found(),
+    })
+    .await;
+    let dir = temp_dir("image_extensions");
+    let downloader = downloader(&server);
+
+    assert_eq!(downloader.download_image("/lightnovel/illusts/a.jpg?v=2", &dir, "001", "").await.unwrap(), "001.png");
+    assert_eq!(downloader.download_image("/lightnovel/illusts/b.png", &dir, "002", "").await.unwrap(), "002.gif");
+    assert!(dir.join("001.png").is_file() && dir.join("002.gif").is_file());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn falls_back_from_magic_bytes_to_content_type_then_url() {
+    let unknown = b"not an image header";
+    // 魔数优先
+    assert_eq!(detect_image_extension(&png_bytes(), "image/jpeg", "https://i.docln.net/a.jpg"), "png");
+    assert_eq!(detect_image_extension(&[0xFF, 0xD8, 0xFF, 0xE0], "image/png", "https://i.docln.net/a.png"), "jpg");
+    assert_eq!(detect_image_extension(b"RIFF\x00\x00\x00\x00WEBPVP8 ", "", "https://i.docln.net/a.jpg"), "webp");
+    // 其次是Content-Type（忽略参数和大小写）
+    assert_eq!(detect_image_extension(unknown, "Image/WebP; charset=binary", "https://i.docln.net/a.jpg"), "webp");
+    assert_eq!(detect_image_extension(unknown, "image/gif", "https://i.docln.net/a.png"), "gif");
+    // 最后才看URL，查询参数不影响
+    assert_eq!(detect_image_extension(unknown, "application/octet-stream", "https://i.docln.net/a.GIF?v=1"), "gif");
+    assert_eq!(detect_image_extension(unknown, "", "https://i.docln.net/a"), "jpg");
+}
@@ -0,0 +1,608 @@
+mod common;
+
+use common::{fixture, png_bytes};
+use docln_fetch::crawler::{ChapterProcessor, NovelParser};
+use docln_fetch::{Chapter, DoclnError, Epub, EpubGenerator, FilenameSuffix, ReadingDirection, Volume};
+use docln_fetch::epub::validate::validate_package;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 在临时目录下准备一个包含两卷、每卷两章的EPUB工作目录
+fn staged_novel(name: &str) -> (Epub, PathBuf) {
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let epub_dir = output_dir.join("epub_1234");
+
+    let mut epub = NovelParser
+        .parse_novel_info(&fixture("series_page.html"), "https://docln.net/sang-tac/1234", 1234)
+        .unwrap();
+    for volume_index in 0..2 {
+        let mut chapters = Vec::new();
+        for chapter_index in 0..2 {
+            let xhtml_path = format!("text/volume_{:03}/chapter_{:03}.xhtml", volume_index + 1, chapter_index + 1);
+            let title = format!("Chương {}", chapter_index + 1);
+            let paragraphs = vec![format!("<p>Tập {} - {}</p>", volume_index + 1, title)];
+            let xhtml = ChapterProcessor::build_chapter_xhtml(&title, None, &paragraphs, &[], "vi");
+            let path = epub_dir.join("OEBPS").join(&xhtml_path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, xhtml).unwrap();
+
+            let mut chapter = Chapter::builder().title(title).url(format!("/c{}{}", volume_index, chapter_index)).build();
+            chapter.xhtml_path = Some(xhtml_path);
+            chapters.push(chapter);
+        }
+        epub.volumes.push(
+            Volume::builder()
+                .title(format!("Tập {:02}", volume_index + 1))
+                .volume_id(format!("#volume_{}", volume_index + 1))
+                .chapters(chapters)
+                .build(),
+        );
+    }
+    (epub, epub_dir)
+}
+
+fn read_entry(epub_path: &Path, name: &str) -> String {
+    let mut archive = zip::ZipArchive::new(fs::File::open(epub_path).unwrap()).unwrap();
+    let mut content = String::new();
+    archive.by_name(name).unwrap().read_to_string(&mut content).unwrap();
+    content
+}
+
+/// 解析带DOCTYPE的XHTML页面
+fn parse_xhtml(xhtml: &str) -> roxmltree::Document<'_> {
+    let options = roxmltree::ParsingOptions { allow_dtd: true, ..Default::default() };
+    roxmltree::Document::parse_with_options(xhtml, options).expect("XHTML should be well-formed")
+}
+
+/// content.opf中声明的书写方向；同时确认spine上没有OPF 2.0不支持的page-progression-direction
+fn writing_mode(content_opf: &str) -> Option<String> {
+    let document = roxmltree::Document::parse(content_opf).unwrap();
+    let spine = document.descendants().find(|node| node.has_tag_name("spine")).unwrap();
+    assert_eq!(spine.attribute("page-progression-direction"), None);
+    document
+        .descendants()
+        .find(|node| node.has_tag_name("meta") && node.attribute("name") == Some("primary-writing-mode"))
+        .and_then(|meta| meta.attribute("content"))
+        .map(str::to_string)
+}
+
+#[test]
+fn generates_combined_epub_by_default() {
+    let (epub, epub_dir) = staged_novel("combined");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+
+    let files = EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate_files().unwrap();
+
+    assert_eq!(files, vec!["docln_1234.epub"]);
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    assert!(content_opf.contains("text/volume_001/chapter_001.xhtml"));
+    assert!(content_opf.contains("text/volume_002/chapter_002.xhtml"));
+    assert!(content_opf.contains(r#"<dc:identifier id="BookId">urn:docln:1234</dc:identifier>"#));
+    assert_eq!(writing_mode(&content_opf), Some("horizontal-lr".to_string()));
+    let toc_ncx = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/toc.ncx");
+    assert!(toc_ncx.contains(r#"<meta name="dtb:uid" content="urn:docln:1234"/>"#));
+    assert!(!epub_dir.exists());
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn generates_one_epub_per_volume() {
+    let (epub, epub_dir) = staged_novel("per_volume");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+
+    let files = EpubGenerator::new(epub)
+        .epub_dir(epub_dir.to_string_lossy())
+        .per_volume(true)
+        .generate_files()
+        .unwrap();
+
+    assert_eq!(files, vec!["docln_1234_vol001.epub", "docln_1234_vol002.epub"]);
+    let content_opf = read_entry(&output_dir.join("docln_1234_vol002.epub"), "OEBPS/content.opf");
+    assert!(content_opf.contains("<dc:title>Thiên Thần Nhà Bên - Tập 02</dc:title>"));
+    assert!(content_opf.contains("<dc:creator opf:role=\"aut\">Saekisan</dc:creator>"));
+    assert!(content_opf.contains("<dc:subject>Romance</dc:subject>"));
+    assert!(content_opf.contains(r#"<dc:identifier id="BookId">urn:docln:1234:vol2</dc:identifier>"#));
+    assert!(content_opf.contains("text/volume_002/chapter_001.xhtml"));
+    assert!(!content_opf.contains("text/volume_001/"));
+    let toc_ncx = read_entry(&output_dir.join("docln_1234_vol002.epub"), "OEBPS/toc.ncx");
+    assert!(toc_ncx.contains("Tập 02"));
+    assert!(!toc_ncx.contains("Tập 01"));
+    assert!(!epub_dir.exists());
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn appends_content_hash_to_filename() {
+    let (epub, epub_dir) = staged_novel("content_hash");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+
+    let filename = EpubGenerator::new(epub)
+        .epub_dir(epub_dir.to_string_lossy())
+        .filename_suffix(FilenameSuffix::ContentHash)
+        .generate()
+        .unwrap();
+
+    let hash = filename.strip_prefix("docln_1234_").and_then(|rest| rest.strip_suffix(".epub")).unwrap();
+    assert_eq!(hash.len(), 8);
+    assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    assert!(output_dir.join(&filename).exists());
+    assert!(!output_dir.join("docln_1234.epub").exists());
+    assert!(!output_dir.join("docln_1234.epub.part").exists());
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn writes_rtl_writing_mode_when_requested() {
+    let (epub, epub_dir) = staged_novel("rtl");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+
+    EpubGenerator::new(epub)
+        .epub_dir(epub_dir.to_string_lossy())
+        .reading_direction(ReadingDirection::Rtl)
+        .generate()
+        .unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    assert_eq!(writing_mode(&content_opf), Some("horizontal-rl".to_string()));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn dates_follow_chapters_and_series_update() {
+    let (mut epub, epub_dir) = staged_novel("dates");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    epub.volumes[0].chapters[0].published = Some("03/02/2023".to_string());
+    epub.volumes[1].chapters[1].published = Some("01/02/2023".to_string());
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    assert!(content_opf.contains(r#"<dc:date opf:event="publication">2023-02-01</dc:date>"#));
+    assert!(content_opf.contains(r#"<dc:date opf:event="modification">2023-05-17</dc:date>"#));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn uses_caller_supplied_identifier() {
+    let (epub, epub_dir) = staged_novel("identifier");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+
+    EpubGenerator::new(epub)
+        .epub_dir(epub_dir.to_string_lossy())
+        .identifier("urn:isbn:9780000000000")
+        .generate()
+        .unwrap();
+
+    let epub_path = output_dir.join("docln_1234.epub");
+    assert!(read_entry(&epub_path, "OEBPS/content.opf").contains(">urn:isbn:9780000000000</dc:identifier>"));
+    assert!(read_entry(&epub_path, "OEBPS/toc.ncx").contains(r#"content="urn:isbn:9780000000000""#));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn writes_genres_as_subjects_and_tags_as_meta() {
+    let (mut epub, epub_dir) = staged_novel("genres");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    epub.genres = vec!["Romance".to_string()];
+    epub.tags = vec!["Slow burn".to_string()];
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    assert!(content_opf.contains("<dc:subject>Romance</dc:subject>"));
+    assert!(!content_opf.contains("<dc:subject>Slow burn</dc:subject>"));
+    assert!(content_opf.contains(r#"<meta name="docln:tag" content="Slow burn"/>"#));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn adds_title_page_for_volumes_without_cover() {
+    let (mut epub, epub_dir) = staged_novel("volume_title");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    epub.volumes.push(Volume::builder().title("Tập 03".to_string()).volume_id("#volume_3".to_string()).build());
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let epub_path = output_dir.join("docln_1234.epub");
+    let title_page = read_entry(&epub_path, "OEBPS/text/volume_002/chapter_000.xhtml");
+    assert!(title_page.contains("<h1>Tập 02</h1>"));
+    let content_opf = read_entry(&epub_path, "OEBPS/content.opf");
+    let spine = &content_opf[content_opf.find("<spine").unwrap()..];
+    assert!(spine.find(r#"idref="chapter2_0""#).unwrap() < spine.find(r#"idref="chapter2_1""#).unwrap());
+    // 没有已下载章节的卷不生成分隔页
+    assert!(!content_opf.contains("chapter3_0"));
+    assert!(read_entry(&epub_path, "OEBPS/toc.ncx").contains(r#"<content src="text/volume_001/chapter_000.xhtml"/>"#));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn lists_chapter_urls_in_reading_order() {
+    let mut epub = NovelParser.parse_novel_info(&fixture("series_page.html"), "https://docln.net/sang-tac/1234", 1234).unwrap();
+    let chapter = |url: &str| Chapter::builder().title("Chương".to_string()).url(url.to_string()).build();
+    epub.volumes.push(Volume::builder().title("Tập 1".to_string()).chapters(vec![chapter("/truyen/1234/c1"), chapter("https://ln.hako.vn/truyen/1234/c2")]).build());
+    epub.volumes.push(Volume::builder().title("Tập 2".to_string()).chapters(vec![chapter("/truyen/1234/c3")]).build());
+
+    assert_eq!(
+        epub.volume_chapter_urls("https://docln.net/"),
+        vec![
+            (0, "https://docln.net/truyen/1234/c1".to_string()),
+            (0, "https://ln.hako.vn/truyen/1234/c2".to_string()),
+            (1, "https://docln.net/truyen/1234/c3".to_string()),
+        ]
+    );
+    assert_eq!(epub.chapter_urls("https://docln.net").len(), 3);
+}
+
+#[test]
+fn lists_volume_illustrators_with_novel_fallback() {
+    let (mut epub, epub_dir) = staged_novel("volume_illustrator");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    epub.volumes[1].illustrator = Some("Mitsuki".to_string());
+
+    let files = EpubGenerator::new(epub.clone())
+        .epub_dir(epub_dir.to_string_lossy())
+        .keep_staging(true)
+        .generate_files()
+        .unwrap();
+    let content_opf = read_entry(&output_dir.join(&files[0]), "OEBPS/content.opf");
+    assert!(content_opf.contains(r#"<dc:contributor opf:role="ill">Hanekoto</dc:contributor>"#));
+    assert!(content_opf.contains(r#"<dc:contributor opf:role="ill">Mitsuki</dc:contributor>"#));
+    let title_page = read_entry(&output_dir.join(&files[0]), "OEBPS/text/volume_002/chapter_000.xhtml");
+    assert!(title_page.contains(r#"<p class="volume-illustrator">插画师: Mitsuki</p>"#));
+
+    let files = EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).per_volume(true).generate_files().unwrap();
+    let first_volume_opf = read_entry(&output_dir.join(&files[0]), "OEBPS/content.opf");
+    assert!(first_volume_opf.contains(r#"<dc:contributor opf:role="ill">Hanekoto</dc:contributor>"#));
+    assert!(!first_volume_opf.contains("Mitsuki"));
+    let second_volume_opf = read_entry(&output_dir.join(&files[1]), "OEBPS/content.opf");
+    assert!(second_volume_opf.contains(r#"<dc:contributor opf:role="ill">Mitsuki</dc:contributor>"#));
+    assert!(!second_volume_opf.contains("Hanekoto"));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn compress_only_repackages_kept_work_dir() {
+    let (epub, epub_dir) = staged_novel("compress_only");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).keep_staging(true).generate().unwrap();
+    let epub_path = output_dir.join("docln_1234.epub");
+    let generated = fs::read(&epub_path).unwrap();
+    fs::remove_file(&epub_path).unwrap();
+
+    assert_eq!(EpubGenerator::compress_only(&epub_dir).unwrap(), "docln_1234.epub");
+
+    assert_eq!(fs::read(&epub_path).unwrap(), generated);
+    assert!(epub_dir.join("OEBPS").join("content.opf").is_file());
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn compress_only_rejects_incomplete_work_dir() {
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_compress_only_invalid_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let epub_dir = output_dir.join("epub_1234");
+    fs::create_dir_all(epub_dir.join("OEBPS")).unwrap();
+    fs::write(epub_dir.join("mimetype"), "application/epub+zip").unwrap();
+
+    assert!(EpubGenerator::compress_only(&epub_dir).is_err());
+    assert!(!output_dir.join("docln_1234.epub").exists());
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn marks_cover_with_opf2_meta_only() {
+    let (mut epub, epub_dir) = staged_novel("cover_meta");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    fs::create_dir_all(epub_dir.join("OEBPS/images")).unwrap();
+    fs::write(epub_dir.join("OEBPS/images/cover.png"), png_bytes()).unwrap();
+    epub.cover_image_path = Some("images/cover.png".to_string());
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    let document = roxmltree::Document::parse(&content_opf).unwrap();
+    assert_eq!(document.root_element().attribute("version"), Some("2.0"));
+    let cover_meta = document.descendants().find(|node| node.has_tag_name("meta") && node.attribute("name") == Some("cover")).unwrap();
+    let cover_item = document.descendants().find(|node| node.has_tag_name("item") && node.attribute("id") == cover_meta.attribute("content")).unwrap();
+    assert_eq!(cover_item.attribute("href"), Some("images/cover.png"));
+    assert!(document.descendants().all(|node| node.attribute("properties").is_none()));
+    let first_itemref = document.descendants().find(|node| node.has_tag_name("itemref")).unwrap();
+    assert_eq!(first_itemref.attribute("idref"), Some("cover"));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn volume_opening_pages_carry_title_and_language() {
+    let (mut epub, epub_dir) = staged_novel("volume_cover_page");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    fs::create_dir_all(epub_dir.join("OEBPS/images")).unwrap();
+    fs::write(epub_dir.join("OEBPS/images/volume_1_cover.png"), png_bytes()).unwrap();
+    epub.volumes[0].cover_image_path = Some("images/volume_1_cover.png".to_string());
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let epub_path = output_dir.join("docln_1234.epub");
+    let xml_lang = ("http://www.w3.org/XML/1998/namespace", "lang");
+
+    // 有卷封面的卷：封面页保留卷标题
+    let cover_page = read_entry(&epub_path, "OEBPS/text/volume_001/chapter_000.xhtml");
+    let document = parse_xhtml(&cover_page);
+    assert_eq!(document.root_element().attribute(xml_lang), Some("vi"));
+    let body = document.descendants().find(|node| node.has_tag_name("body")).unwrap();
+    assert_eq!(body.attribute("class"), Some("cover-page"));
+    let heading = document.descendants().find(|node| node.has_tag_name("h1")).unwrap();
+    assert_eq!(heading.text(), Some("Tập 01"));
+    let image = document.descendants().find(|node| node.has_tag_name("img")).unwrap();
+    assert_eq!(image.attribute("src"), Some("../../images/volume_1_cover.png"));
+    assert_eq!(image.attribute("class"), Some("volume-cover-img"));
+
+    // 没有卷封面的卷：标题分隔页
+    let title_page = read_entry(&epub_path, "OEBPS/text/volume_002/chapter_000.xhtml");
+    let document = parse_xhtml(&title_page);
+    assert_eq!(document.root_element().attribute(xml_lang), Some("vi"));
+    assert_eq!(document.descendants().find(|node| node.has_tag_name("body")).unwrap().attribute("class"), Some("volume-title-page"));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn gallery_lists_illustrations_in_chapter_order() {
+    let (mut epub, epub_dir) = staged_novel("illustration_gallery");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    // 第二章的插图先下载完成也不影响顺序：插图集按章节、再按章内编号排列
+    let illustrations = [
+        (1, "images/volume_001/chapter_002/001.png"),
+        (0, "images/volume_001/chapter_001/001.png"),
+        (0, "images/volume_001/chapter_001/002.png"),
+    ];
+    for (chapter_index, path) in illustrations {
+        let file = epub_dir.join("OEBPS").join(path);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, png_bytes()).unwrap();
+        epub.volumes[0].chapters[chapter_index].illustration_paths.push(path.to_string());
+    }
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).illustration_gallery(true).generate().unwrap();
+
+    let epub_path = output_dir.join("docln_1234.epub");
+    let gallery = read_entry(&epub_path, "OEBPS/text/volume_001/gallery.xhtml");
+    let document = parse_xhtml(&gallery);
+    assert_eq!(document.root_element().attribute(("http://www.w3.org/XML/1998/namespace", "lang")), Some("vi"));
+    let sources: Vec<&str> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("img"))
+        .filter_map(|img| img.attribute("src"))
+        .collect();
+    assert_eq!(
+        sources,
+        [
+            "../../images/volume_001/chapter_001/001.png",
+            "../../images/volume_001/chapter_001/002.png",
+            "../../images/volume_001/chapter_002/001.png",
+        ]
+    );
+
+    // 没有插图的卷不生成插图集；插图集在spine中位于该卷的章节之前
+    let mut archive = zip::ZipArchive::new(fs::File::open(&epub_path).unwrap()).unwrap();
+    assert!(archive.by_name("OEBPS/text/volume_002/gallery.xhtml").is_err());
+    let content_opf = read_entry(&epub_path, "OEBPS/content.opf");
+    let document = roxmltree::Document::parse(&content_opf).unwrap();
+    let spine: Vec<&str> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("itemref"))
+        .filter_map(|itemref| itemref.attribute("idref"))
+        .collect();
+    let gallery_position = spine.iter().position(|idref| *idref == "gallery1").unwrap();
+    assert_eq!(spine[gallery_position + 1], "chapter1_1");
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn validates_opf_as_xml_with_decoded_hrefs() {
+    let content_opf = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="BookId">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+        <dc:title>Tôi &amp; em &lt;3</dc:title>
+        <meta name="cover" content="cover-image"/>
+    </metadata>
+    <manifest>
+        <item id="cover-image" href="images/b%C3%ACa%20tr%C6%B0%E1%BB%9Bc.png" media-type="image/png"/>
+        <item id="chapter1_1" href="text/volume_001/chapter_001.xhtml" media-type="application/xhtml+xml"/>
+    </manifest>
+    <spine toc="ncx">
+        <itemref idref="chapter1_1"/>
+    </spine>
+    <guide>
+        <reference type="text" title="Tôi &amp; em" href="text/volume_001/chapter_001.xhtml#start"/>
+    </guide>
+</package>"#;
+    let files = ["images/bìa trước.png", "text/volume_001/chapter_001.xhtml"];
+    validate_package(content_opf, |href| files.contains(&href)).unwrap();
+
+    // 缺少的文件和悬空的引用都会列出
+    let missing = content_opf.replace("chapter_001.xhtml\" media", "chapter_009.xhtml\" media").replace("idref=\"chapter1_1\"", "idref=\"chapter1_2\"");
+    let Err(DoclnError::Epub(message)) = validate_package(&missing, |href| files.contains(&href)) else {
+        panic!("missing files should fail validation");
+    };
+    assert!(message.contains("text/volume_001/chapter_009.xhtml"), "{}", message);
+    assert!(message.contains("chapter1_2"), "{}", message);
+
+    // 不是格式正确的XML时直接报错，而不是像HTML解析器那样容错
+    let malformed = content_opf.replace("</manifest>", "");
+    assert!(matches!(validate_package(&malformed, |_| true), Err(DoclnError::Epub(_))));
+}
+
+#[test]
+fn escapes_markup_in_titles_across_package_files() {
+    let title = "Tôi & em <3";
+    let (mut epub, epub_dir) = staged_novel("escaped_titles");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    epub.title = title.to_string();
+    epub.author = "A & B <tác giả>".to_string();
+    epub.volumes[0].title = format!("Tập 1: {}", title);
+    // 章节页面由ChapterProcessor生成，标题同样需要转义
+    let chapter_title = format!("Chương 1: \"{}\"", title);
+    let xhtml = ChapterProcessor::build_chapter_xhtml(&chapter_title, None, &["<p>Nội dung</p>".to_string()], &[], "vi");
+    fs::write(epub_dir.join("OEBPS/text/volume_001/chapter_001.xhtml"), xhtml).unwrap();
+    epub.volumes[0].chapters[0].title = chapter_title.clone();
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let epub_path = output_dir.join("docln_1234.epub");
+    let content_opf = read_entry(&epub_path, "OEBPS/content.opf");
+    let document = roxmltree::Document::parse(&content_opf).expect("content.opf should be well-formed");
+    let text_of = |document: &roxmltree::Document, name: &str| {
+        document.descendants().find(|node| node.has_tag_name(name)).and_then(|node| node.text()).map(str::to_string)
+    };
+    assert_eq!(text_of(&document, "title").as_deref(), Some(title));
+    assert_eq!(text_of(&document, "creator").as_deref(), Some("A & B <tác giả>"));
+
+    let toc_ncx = read_entry(&epub_path, "OEBPS/toc.ncx");
+    let document = parse_xhtml(&toc_ncx);
+    let doc_title = document.descendants().find(|node| node.has_tag_name("docTitle")).unwrap();
+    assert_eq!(doc_title.descendants().find(|node| node.has_tag_name("text")).unwrap().text(), Some(title));
+    let labels: Vec<&str> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("navLabel"))
+        .filter_map(|label| label.descendants().find(|node| node.has_tag_name("text")).and_then(|text| text.text()))
+        .collect();
+    assert!(labels.contains(&format!("Tập 1: {}", title).as_str()), "{:?}", labels);
+    assert!(labels.contains(&chapter_title.as_str()), "{:?}", labels);
+
+    for page in ["OEBPS/text/title.xhtml", "OEBPS/text/volume_001/chapter_000.xhtml"] {
+        let xhtml = read_entry(&epub_path, page);
+        parse_xhtml(&xhtml);
+    }
+    let chapter_page = read_entry(&epub_path, "OEBPS/text/volume_001/chapter_001.xhtml");
+    let document = parse_xhtml(&chapter_page);
+    assert_eq!(document.descendants().find(|node| node.has_tag_name("title")).unwrap().text(), Some(chapter_title.as_str()));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+/// content.opf中manifest条目的id，按出现顺序
+fn manifest_ids(content_opf: &str) -> Vec<String> {
+    let document = roxmltree::Document::parse(content_opf).unwrap();
+    document
+        .descendants()
+        .filter(|node| node.has_tag_name("item") && node.parent_element().is_some_and(|parent| parent.has_tag_name("manifest")))
+        .filter_map(|item| item.attribute("id"))
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn gives_every_image_a_unique_manifest_id() {
+    let (mut epub, epub_dir) = staged_novel("unique_manifest_ids");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    // 两张插图的文件名按旧规则替换特殊字符后相同（"a b" 和 "a_b"），两卷封面也是如此
+    let images = [
+        (Some((0, 0)), "images/a b.png"),
+        (Some((0, 1)), "images/a_b.png"),
+        (None, "images/volume_001_Tập 01.png"),
+        (None, "images/volume_002_Tập_01.png"),
+    ];
+    for (chapter, path) in images {
+        fs::create_dir_all(epub_dir.join("OEBPS/images")).unwrap();
+        fs::write(epub_dir.join("OEBPS").join(path), png_bytes()).unwrap();
+        if let Some((volume_index, chapter_index)) = chapter {
+            epub.volumes[volume_index].chapters[chapter_index].illustration_paths.push(path.to_string());
+        }
+    }
+    epub.volumes[0].cover_image_path = Some(images[2].1.to_string());
+    epub.volumes[1].cover_image_path = Some(images[3].1.to_string());
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    let ids = manifest_ids(&content_opf);
+    let unique: std::collections::HashSet<&String> = ids.iter().collect();
+    assert_eq!(unique.len(), ids.len(), "{:?}", ids);
+    let document = roxmltree::Document::parse(&content_opf).unwrap();
+    let image_items = document
+        .descendants()
+        .filter(|node| node.has_tag_name("item") && node.attribute("media-type") == Some("image/png"))
+        .count();
+    assert_eq!(image_items, images.len());
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+/// XML NCName：以字母或下划线开头，其余为字母、数字、`.`、`-`、`_`，不含冒号
+fn is_ncname(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars.next().is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+#[test]
+fn manifest_ids_are_ncnames() {
+    let (mut epub, epub_dir) = staged_novel("ncname_manifest_ids");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    // 以数字开头、含点号和空格的插图文件名
+    let images = ["images/volume_001/chapter_001/001.png", "images/1st illust.v2.png"];
+    for (chapter_index, path) in images.into_iter().enumerate() {
+        let file = epub_dir.join("OEBPS").join(path);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, png_bytes()).unwrap();
+        epub.volumes[0].chapters[chapter_index].illustration_paths.push(path.to_string());
+    }
+    fs::write(epub_dir.join("OEBPS/images/2nd.png"), png_bytes()).unwrap();
+    epub.volumes[1].cover_image_path = Some("images/2nd.png".to_string());
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).illustration_gallery(true).generate().unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    let ids = manifest_ids(&content_opf);
+    assert!(ids.len() > images.len());
+    for id in &ids {
+        assert!(is_ncname(id), "manifest id不是合法的NCName: {}", id);
+    }
+    assert!(!is_ncname("1st") && !is_ncname("a:b") && is_ncname("img_v001_c001_001"));
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn lists_every_volume_illustrator_as_contributor() {
+    let (mut epub, epub_dir) = staged_novel("volume_contributors");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    // 小说没有标注插画师；第二卷的章节还没有下载，插画师仍属于这本书
+    epub.illustrator = None;
+    epub.volumes[0].illustrator = Some("Hanekoto".to_string());
+    epub.volumes[1].illustrator = Some("Mitsuki".to_string());
+    epub.volumes.push(Volume::builder().title("Tập 03".to_string()).volume_id("#volume_3".to_string()).build());
+    epub.volumes[2].illustrator = Some("Hanekoto".to_string());
+    for chapter in &mut epub.volumes[1].chapters {
+        chapter.xhtml_path = None;
+    }
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    let document = roxmltree::Document::parse(&content_opf).unwrap();
+    let illustrators: Vec<&str> = document
+        .descendants()
+        .filter(|node| node.has_tag_name("contributor"))
+        .filter(|node| node.attribute(("http://www.idpf.org/2007/opf", "role")) == Some("ill"))
+        .filter_map(|node| node.text())
+        .collect();
+    assert_eq!(illustrators, ["Hanekoto", "Mitsuki"]);
+    fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn lists_other_titles_after_main_title_without_ids() {
+    let (epub, epub_dir) = staged_novel("other_titles");
+    let output_dir = epub_dir.parent().unwrap().to_path_buf();
+    let expected: Vec<String> = std::iter::once(epub.title.clone()).chain(epub.other_titles.clone()).collect();
+    assert!(expected.len() > 1);
+
+    EpubGenerator::new(epub).epub_dir(epub_dir.to_string_lossy()).generate().unwrap();
+
+    let content_opf = read_entry(&output_dir.join("docln_1234.epub"), "OEBPS/content.opf");
+    let document = roxmltree::Document::parse(&content_opf).unwrap();
+    let titles: Vec<_> = document.descendants().filter(|node| node.has_tag_name("title")).collect();
+    assert_eq!(titles.iter().filter_map(|title| title.text()).collect::<Vec<_>>(), expected);
+    // OPF 2.0没有引用这些id的meta，附加标题不带id
+    assert!(titles.iter().all(|title| title.attribute("id").is_none()));
+    fs::remove_dir_all(output_dir).unwrap();
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::png_bytes;
+use docln_fetch::DoclnError;
+use docln_fetch::crawler::image_guard::{check_image_dimensions, DEFAULT_MAX_IMAGE_PIXELS};
+
+/// 只有签名和IHDR块的PNG头部，声明给定的宽高
+fn png_header(width: u32, height: u32) -> Vec<u8> {
+    let mut header = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+    header
+}
+
+#[test]
+fn accepts_images_within_pixel_limit() {
+    assert!(check_image_dimensions(&png_bytes(), DEFAULT_MAX_IMAGE_PIXELS).is_ok());
+    assert!(check_image_dimensions(&png_header(10_000, 10_000), DEFAULT_MAX_IMAGE_PIXELS).is_ok());
+}
+
+#[test]
+fn rejects_oversized_dimensions_from_header() {
+    // 头部声明 100000x100000，不需要完整数据即可拒绝
+    let result = check_image_dimensions(&png_header(100_000, 100_000), DEFAULT_MAX_IMAGE_PIXELS);
+    assert!(matches!(result, Err(DoclnError::Image(_))));
+    assert!(check_image_dimensions(&png_header(2_000, 2_000), 1_000_000).is_err());
+}
+
+#[test]
+fn rejects_data_without_recognizable_header() {
+    for bytes in [&b"<html><body>404</body></html>"[..], &b""[..], &b"\x89PNG"[..]] {
+        assert!(matches!(check_image_dimensions(bytes, DEFAULT_MAX_IMAGE_PIXELS), Err(DoclnError::Image(_))));
+    }
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "image-resize")]
+
+use docln_fetch::crawler::image_resize::optimize_image;
+use docln_fetch::crawler::downloader::detect_image_extension;
+use docln_fetch::ImageResizeOptions;
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage, Rgba, RgbaImage};
+use std::io::Cursor;
+
+fn encode(image: DynamicImage, format: ImageFormat) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    image.write_to(&mut Cursor::new(&mut bytes), format).unwrap();
+    bytes
+}
+
+fn gradient(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_fn(width, height, |x, y| Rgb([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8]))
+}
+
+#[test]
+fn downscales_large_images_to_jpeg() {
+    let options = ImageResizeOptions { max_dimension: 500, jpeg_quality: 80 };
+    let original = encode(DynamicImage::ImageRgb8(gradient(1200, 800)), ImageFormat::Png);
+
+    let optimized = optimize_image(&original, &options).unwrap().unwrap();
+    let resized = image::load_from_memory(&optimized).unwrap();
+    assert_eq!((resized.width(), resized.height()), (500, 333));
+    // 扩展名按新内容确定，manifest的media-type随之一致
+    assert_eq!(detect_image_extension(&optimized, "image/png", "https://i.docln.net/a.png"), "jpg");
+}
+
+#[test]
+fn keeps_transparent_images_as_png() {
+    let options = ImageResizeOptions { max_dimension: 100, jpeg_quality: 80 };
+    let original = encode(DynamicImage::ImageRgba8(RgbaImage::from_pixel(300, 200, Rgba([10, 20, 30, 128]))), ImageFormat::Png);
+
+    let optimized = optimize_image(&original, &options).unwrap().unwrap();
+    assert_eq!(image::guess_format(&optimized).unwrap(), ImageFormat::Png);
+    let resized = image::load_from_memory(&optimized).unwrap();
+    assert_eq!((resized.width(), resized.height()), (100, 67));
+}
+
+#[test]
+fn leaves_small_non_jpeg_images_alone() {
+    let options = ImageResizeOptions::default();
+    let original = encode(DynamicImage::ImageRgb8(gradient(64, 64)), ImageFormat::Png);
+
+    assert_eq!(optimize_image(&original, &options).unwrap(), None);
+}
+
+#[test]
+fn rejects_undecodable_data() {
+    assert!(optimize_image(b"not an image", &ImageResizeOptions::default()).is_err());
+}
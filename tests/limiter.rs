@@ -0,0 +1,57 @@
+mod common;
+
+use common::{novel_site, MockServer};
+use docln_fetch::DoclnCrawler;
+use docln_fetch::crawler::RequestDelay;
+use std::fs;
+use std::time::Duration;
+
+#[test]
+fn samples_delay_within_range() {
+    let delay = RequestDelay::new(Duration::from_millis(400), Duration::from_millis(1200));
+    for _ in 0..100 {
+        let sample = delay.sample();
+        assert!(sample >= Duration::from_millis(400) && sample <= Duration::from_millis(1200));
+    }
+}
+
+#[test]
+fn swaps_reversed_bounds_and_supports_fixed_delay() {
+    let delay = RequestDelay::new(Duration::from_millis(900), Duration::from_millis(300));
+    assert_eq!((delay.min(), delay.max()), (Duration::from_millis(300), Duration::from_millis(900)));
+
+    let fixed = RequestDelay::from(Duration::from_millis(500));
+    assert_eq!(fixed.sample(), Duration::from_millis(500));
+    assert!(RequestDelay::fixed(Duration::ZERO).sample().is_zero());
+}
+
+#[test]
+fn raises_delay_to_floor() {
+    let delay = RequestDelay::new(Duration::from_millis(400), Duration::from_millis(1200)).at_least(Duration::from_secs(1));
+    assert_eq!((delay.min(), delay.max()), (Duration::from_secs(1), Duration::from_millis(1200)));
+}
+
+#[tokio::test]
+async fn crawl_keeps_global_in_flight_requests_under_cap() {
+    // 每个响应都稍作延迟，让章节和插图请求有机会同时进行
+    let server = MockServer::start(|path| novel_site(path).delay(Duration::from_millis(30))).await;
+    let output_dir = std::env::temp_dir().join(format!("docln_fetch_test_in_flight_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&output_dir);
+    let crawler = DoclnCrawler::builder()
+        .base_url(&server.url)
+        .unwrap()
+        .output_dir(&output_dir)
+        .request_limits(2, 4, 4)
+        .volume_concurrency(2)
+        .chapter_concurrency(4)
+        .image_concurrency(4)
+        .request_delay(Duration::ZERO)
+        .verbose(false)
+        .build();
+
+    crawler.crawl_to_epub(1234).await.unwrap();
+
+    assert!(server.requests().len() > 2);
+    assert_eq!(server.max_in_flight(), 2);
+    fs::remove_dir_all(output_dir).unwrap();
+}
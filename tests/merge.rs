@@ -0,0 +1,52 @@
+mod common;
+
+use common::fixture;
+use docln_fetch::crawler::{ChapterProcessor, NovelParser};
+use docln_fetch::crawler::merge::{merge_split_chapters, split_part_title};
+use docln_fetch::{Chapter, Volume};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn recognizes_part_suffixes() {
+    assert_eq!(split_part_title("Chương 5 - Phần 1"), Some(("Chương 5".to_string(), 1)));
+    assert_eq!(split_part_title("Chương 5 (Part 2)"), Some(("Chương 5".to_string(), 2)));
+    assert_eq!(split_part_title("PHẦN 2"), Some((String::new(), 2)));
+    assert_eq!(split_part_title("Chương 12"), None);
+    assert_eq!(split_part_title("Counterpart 2"), None);
+}
+
+/// 写入一个只含一段正文的章节，返回已下载的章节
+fn downloaded_chapter(oebps_dir: &Path, index: usize, title: &str, text: &str) -> Chapter {
+    let xhtml_path = format!("text/volume_001/chapter_{:03}.xhtml", index);
+    let paragraphs = vec![format!("<p>{}</p>", text)];
+    fs::write(oebps_dir.join(&xhtml_path), ChapterProcessor::build_chapter_xhtml(title, None, &paragraphs, &[], "vi")).unwrap();
+    let mut chapter = Chapter::builder().title(title.to_string()).url(format!("/c{}", index)).xhtml_path(Some(xhtml_path)).build();
+    chapter.illustration_paths = vec![format!("images/volume_001/chapter_{:03}/001.jpg", index)];
+    chapter
+}
+
+#[test]
+fn merges_consecutive_parts_into_one_chapter() {
+    let oebps_dir = std::env::temp_dir().join(format!("docln_fetch_test_merge_{}", std::process::id())).join("OEBPS");
+    fs::create_dir_all(oebps_dir.join("text/volume_001")).unwrap();
+    let chapters = vec![
+        downloaded_chapter(&oebps_dir, 1, "Chương 5 - Phần 1", "Mở đầu."),
+        downloaded_chapter(&oebps_dir, 2, "Phần 2", "Kết thúc."),
+        downloaded_chapter(&oebps_dir, 3, "Chương 6", "Chương mới."),
+    ];
+    let mut epub = NovelParser.parse_novel_info(&fixture("series_page.html"), "https://docln.net/sang-tac/1234", 1234).unwrap();
+    epub.volumes.push(Volume::builder().title("Tập 1".to_string()).chapters(chapters).build());
+
+    assert_eq!(merge_split_chapters(&mut epub, &oebps_dir).unwrap(), 1);
+
+    let chapters = &epub.volumes[0].chapters;
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[0].title, "Chương 5");
+    assert_eq!(chapters[0].xhtml_path.as_deref(), Some("text/volume_001/chapter_001_merged.xhtml"));
+    assert_eq!(chapters[0].illustration_paths.len(), 2);
+    let merged = fs::read_to_string(oebps_dir.join("text/volume_001/chapter_001_merged.xhtml")).unwrap();
+    assert!(merged.find("Mở đầu.").unwrap() < merged.find("Kết thúc.").unwrap());
+    assert!(oebps_dir.join("text/volume_001/chapter_002.xhtml").exists());
+    fs::remove_dir_all(oebps_dir.parent().unwrap()).unwrap();
+}
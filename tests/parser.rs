@@ -0,0 +1,278 @@
+mod common;
+
+use common::fixture;
+use docln_fetch::{MetadataOverrides, NovelStatus, normalize_url, normalize_whitespace};
+use docln_fetch::crawler::NovelParser;
+use scraper::Html;
+
+const SERIES_URL: &str = "https://docln.net/sang-tac/1234-thien-than-nha-ben";
+
+#[test]
+fn parses_series_metadata() {
+    let epub = NovelParser.parse_novel_info(&fixture("series_page.html"), SERIES_URL, 1234).unwrap();
+
+    assert_eq!(epub.id, 1234);
+    assert_eq!(epub.title, "Thiên Thần Nhà Bên");
+    assert_eq!(epub.author, "Saekisan");
+    assert_eq!(epub.illustrator.as_deref(), Some("Hanekoto"));
+    assert_eq!(epub.status, Some(NovelStatus::Ongoing));
+    assert_eq!(epub.other_titles, vec!["お隣の天使様にいつの間にか駄目人間にされていた件", "Otonari no Tenshi-sama"]);
+    assert_eq!(epub.tags, vec!["Comedy", "Romance", "School Life"]);
+    assert!(epub.genres.is_empty());
+    assert_eq!(epub.summary.len(), 2);
+    assert_eq!(epub.rating, Some(4.8));
+    assert_eq!(epub.views, Some(1_234_567));
+    assert_eq!(epub.likes, Some(12_345));
+    assert_eq!(epub.updated.as_deref(), Some("2023-05-17"));
+    assert_eq!(epub.url, SERIES_URL);
+    assert!(epub.volumes.is_empty());
+}
+
+#[test]
+fn separates_genres_from_user_tags() {
+    let epub = NovelParser.parse_novel_info(&fixture("series_tags_page.html"), SERIES_URL, 1234).unwrap();
+
+    assert_eq!(epub.genres, vec!["Comedy", "Romance", "School Life"]);
+    assert_eq!(epub.tags, vec!["Ngọt ngào", "Slow burn"]);
+}
+
+#[test]
+fn metadata_overrides_replace_only_given_fields() {
+    let mut epub = NovelParser.parse_novel_info(&fixture("series_page.html"), SERIES_URL, 1234).unwrap();
+    let path = std::env::temp_dir().join(format!("docln_fetch_test_overrides_{}.json", std::process::id()));
+    std::fs::write(&path, r#"{"author": "Saeki-san", "tags": ["Romance"]}"#).unwrap();
+
+    MetadataOverrides::load(&path).unwrap().apply(&mut epub);
+    std::fs::write(&path, r#"{"autor": "typo"}"#).unwrap();
+    let invalid = MetadataOverrides::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(epub.author, "Saeki-san");
+    assert_eq!(epub.tags, vec!["Romance"]);
+    assert_eq!(epub.title, "Thiên Thần Nhà Bên");
+    assert_eq!(epub.illustrator.as_deref(), Some("Hanekoto"));
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn reads_plain_text_author_and_illustrator() {
+    let linked = NovelParser.parse_novel_info(&fixture("series_page.html"), SERIES_URL, 1234).unwrap();
+    let plain = NovelParser.parse_novel_info(&fixture("series_plain_info_page.html"), SERIES_URL, 1234).unwrap();
+
+    assert_eq!(plain.author, linked.author);
+    assert_eq!(plain.illustrator, linked.illustrator);
+    assert_eq!(plain.author, "Saekisan");
+    assert_eq!(plain.illustrator.as_deref(), Some("Hanekoto"));
+}
+
+#[test]
+fn reads_illustrator_from_volume_header() {
+    let document = Html::parse_document(&fixture("series_plain_info_page.html"));
+
+    assert_eq!(NovelParser.parse_volume_illustrator(&document, "#volume_101"), None);
+    assert_eq!(NovelParser.parse_volume_illustrator(&document, "#volume_102").as_deref(), Some("Mitsuki"));
+}
+
+#[test]
+fn empty_author_is_a_parse_error() {
+    let html = fixture("series_plain_info_page.html").replace("Saekisan", " ");
+    assert!(NovelParser.parse_novel_info(&html, SERIES_URL, 1234).is_err());
+}
+
+#[test]
+fn missing_title_is_a_parse_error() {
+    let result = NovelParser.parse_novel_info("<html><body></body></html>", SERIES_URL, 1234);
+    assert!(result.is_err());
+}
+
+#[test]
+fn extracts_cover_urls() {
+    let document = Html::parse_document(&fixture("series_page.html"));
+
+    assert_eq!(
+        NovelParser.extract_cover_url(&document).as_deref(),
+        Some("https://i.docln.net/lightnovel/covers/s1234-cover.jpg")
+    );
+    assert_eq!(
+        NovelParser.extract_volume_cover_url(&document, "#volume_102").as_deref(),
+        Some("https://i.docln.net/lightnovel/covers/v102-cover.jpg")
+    );
+}
+
+#[test]
+fn extracts_cover_url_from_style_variants() {
+    let cover_url = "https://i.docln.net/lightnovel/covers/s1234-cover.jpg";
+    for style in [
+        format!("background-image: url('{}')", cover_url),
+        format!("background-image: url(\"{}\")", cover_url),
+        format!("background-image: url({})", cover_url),
+        format!("background-size: cover;background-image:URL(  '{}'  );", cover_url),
+    ] {
+        let html = format!(r#"<div class="content img-in-ratio" style="{}"></div>"#, style.replace('"', "&quot;"));
+        let document = Html::parse_document(&html);
+        assert_eq!(NovelParser.extract_cover_url(&document).as_deref(), Some(cover_url), "{}", style);
+    }
+}
+
+#[test]
+fn falls_back_to_cover_img_and_og_image() {
+    let html = r#"<div class="series-cover"><div class="content img-in-ratio" style="background-image: none">
+        <img src="https://i.docln.net/lightnovel/covers/s1-img.jpg"></div></div>
+        <meta property="og:image" content="https://i.docln.net/lightnovel/covers/s1-og.jpg">"#;
+    let document = Html::parse_document(html);
+    assert_eq!(NovelParser.extract_cover_url(&document).as_deref(), Some("https://i.docln.net/lightnovel/covers/s1-img.jpg"));
+
+    let html = r#"<meta property="og:image" content=" https://i.docln.net/lightnovel/covers/s1-og.jpg ">"#;
+    let document = Html::parse_document(html);
+    assert_eq!(NovelParser.extract_cover_url(&document).as_deref(), Some("https://i.docln.net/lightnovel/covers/s1-og.jpg"));
+    assert_eq!(NovelParser.extract_cover_url(&Html::parse_document("<div></div>")), None);
+}
+
+#[test]
+fn parses_volumes_and_chapters() {
+    let document = Html::parse_document(&fixture("series_page.html"));
+
+    let volumes = NovelParser.parse_volume_info(&document);
+    assert_eq!(
+        volumes,
+        vec![
+            ("Tập 01".to_string(), "#volume_101".to_string()),
+            ("Tập 02".to_string(), "#volume_102".to_string()),
+        ]
+    );
+
+    let chapters = NovelParser.parse_volume_chapters(&document, "#volume_101");
+    assert_eq!(chapters.len(), 3);
+    assert_eq!(chapters[1].title, "Chương 1: Thiên thần và chiếc ô");
+    assert_eq!(chapters[1].url, "/sang-tac/1234-thien-than-nha-ben/c1002-chuong-1");
+    assert_eq!(chapters[0].published.as_deref(), Some("01/02/2023"));
+    assert_eq!(chapters[2].published, None);
+    assert_eq!(
+        chapters.iter().map(|chapter| chapter.has_illustrations).collect::<Vec<_>>(),
+        vec![true, false, false]
+    );
+    assert!(chapters.iter().all(|chapter| chapter.xhtml_path.is_none()));
+
+    let chapters = NovelParser.parse_volume_chapters(&document, "#volume_102");
+    assert_eq!(chapters.len(), 2);
+    assert!(chapters[0].has_illustrations);
+}
+
+#[test]
+fn finds_next_chapter_page() {
+    let document = Html::parse_document(&fixture("series_page.html"));
+
+    assert_eq!(NovelParser.extract_next_chapter_page_url(&document, "#volume_101"), None);
+    assert_eq!(
+        NovelParser.extract_next_chapter_page_url(&document, "#volume_102").as_deref(),
+        Some("/sang-tac/1234-thien-than-nha-ben/t102-tap-02?page=2")
+    );
+    // 以数字开头或含CSS特殊字符的卷ID不会导致选择器解析失败
+    assert_eq!(NovelParser.extract_next_chapter_page_url(&document, "#102"), None);
+    assert_eq!(NovelParser.extract_next_chapter_page_url(&document, "#vol\"ume"), None);
+}
+
+#[test]
+fn parses_flat_chapter_list_without_volumes() {
+    let html = fixture("oneshot_page.html");
+    let document = Html::parse_document(&html);
+
+    let epub = NovelParser.parse_novel_info(&html, "https://docln.net/sang-tac/5678", 5678).unwrap();
+    assert_eq!(epub.title, "Truyện ngắn mùa hè");
+    assert_eq!(epub.status, Some(NovelStatus::Completed));
+    assert_eq!(epub.illustrator, None);
+    assert_eq!(epub.rating, None);
+
+    assert!(NovelParser.parse_volume_info(&document).is_empty());
+    let chapters = NovelParser.parse_flat_chapters(&document);
+    assert_eq!(chapters.iter().map(|chapter| chapter.title.as_str()).collect::<Vec<_>>(), vec!["Phần 1", "Phần 2"]);
+}
+
+#[test]
+fn collapses_whitespace_in_parsed_text() {
+    let html = "<span class=\"series-name\"><a href=\"/sang-tac/1\">\n    Thiên\u{a0}Thần\n\n    Nhà  Bên </a></span>\
+        <div class=\"info-item\"><span class=\"info-name\">Tác giả:</span>\
+        <span class=\"info-value\"><a href=\"/tac-gia/1\">\t Saeki\u{a0}\u{a0}san\n</a></span></div>\
+        <div class=\"series-gernes\"><a href=\"/the-loai/1\">\n  School\n  Life  </a></div>";
+    let epub = NovelParser.parse_novel_info(html, SERIES_URL, 1).unwrap();
+
+    assert_eq!(epub.title, "Thiên Thần Nhà Bên");
+    assert_eq!(epub.author, "Saeki san");
+    assert_eq!(epub.tags, vec!["School Life"]);
+    assert_eq!(normalize_whitespace(" \u{a0}a \n\n b\t"), "a b");
+}
+
+#[test]
+fn skips_repeated_chapter_entries() {
+    // 第一卷的章节列表末尾重复列出了第一章
+    let document = Html::parse_document(&fixture("series_page.html"));
+    let chapters = NovelParser.parse_volume_chapters(&document, "#volume_101");
+
+    assert_eq!(
+        chapters.iter().map(|chapter| chapter.url.as_str()).collect::<Vec<_>>(),
+        vec![
+            "/sang-tac/1234-thien-than-nha-ben/c1001-minh-hoa",
+            "/sang-tac/1234-thien-than-nha-ben/c1002-chuong-1",
+            "/sang-tac/1234-thien-than-nha-ben/c1003-chuong-2",
+        ]
+    );
+}
+
+#[test]
+fn resolves_image_urls_of_every_shape_against_chapter_url() {
+    let chapter_url = "https://docln.net/sang-tac/1234-thien-than-nha-ben/c1002-chuong-1";
+    // 绝对地址原样保留
+    assert_eq!(normalize_url("https://i.docln.net/lightnovel/a.jpg", chapter_url), "https://i.docln.net/lightnovel/a.jpg");
+    // 协议相对地址补全为https
+    assert_eq!(normalize_url("//i.docln.net/lightnovel/a.jpg", chapter_url), "https://i.docln.net/lightnovel/a.jpg");
+    // 根相对地址按站点解析
+    assert_eq!(normalize_url("/lightnovel/a.jpg", chapter_url), "https://docln.net/lightnovel/a.jpg");
+    // 文档相对地址按章节页面所在目录解析
+    assert_eq!(normalize_url("img/a.jpg", chapter_url), "https://docln.net/sang-tac/1234-thien-than-nha-ben/img/a.jpg");
+    assert_eq!(normalize_url("./a.jpg", chapter_url), "https://docln.net/sang-tac/1234-thien-than-nha-ben/a.jpg");
+    assert_eq!(normalize_url("../a.jpg", chapter_url), "https://docln.net/sang-tac/a.jpg");
+    assert_eq!(normalize_url(" ../../lightnovel/a.jpg ", chapter_url), "https://docln.net/lightnovel/a.jpg");
+    // 站点根地址作为基准时，根相对地址与原来一样直接拼接
+    assert_eq!(normalize_url("/sang-tac/1234", "https://docln.net/"), "https://docln.net/sang-tac/1234");
+    assert_eq!(normalize_url("", chapter_url), "");
+}
+
+#[test]
+fn resolves_volume_sections_in_nested_and_flat_layouts() {
+    // 嵌套布局：header包在标题栏中，与卷内容不是兄弟节点；平铺布局：所有卷的header和内容在同一容器中
+    for name in ["series_nested_layout.html", "series_flat_layout.html"] {
+        let document = Html::parse_document(&fixture(name));
+
+        for (volume_id, volume_number) in [("#volume_301", 1), ("#volume_302", 2)] {
+            let chapters = NovelParser.parse_volume_chapters(&document, volume_id);
+            assert_eq!(
+                chapters.iter().map(|chapter| chapter.url.as_str()).collect::<Vec<_>>(),
+                vec![
+                    format!("/sang-tac/1234-thien-than-nha-ben/c30{}1-chuong-1", volume_number),
+                    format!("/sang-tac/1234-thien-than-nha-ben/c30{}2-chuong-2", volume_number),
+                ],
+                "{} {}",
+                name,
+                volume_id
+            );
+            assert_eq!(
+                NovelParser.extract_volume_cover_url(&document, volume_id),
+                Some(format!("https://i.docln.net/lightnovel/covers/v30{}-cover.jpg", volume_number)),
+                "{} {}",
+                name,
+                volume_id
+            );
+        }
+
+        // 插画师和分页链接只属于第二卷，不会被第一卷取到，也不会取到小说信息栏中的插画师
+        assert_eq!(NovelParser.parse_volume_illustrator(&document, "#volume_301"), None, "{}", name);
+        assert_eq!(NovelParser.parse_volume_illustrator(&document, "#volume_302").as_deref(), Some("Mitsuki"), "{}", name);
+        assert_eq!(NovelParser.extract_next_chapter_page_url(&document, "#volume_301"), None, "{}", name);
+        assert_eq!(
+            NovelParser.extract_next_chapter_page_url(&document, "#volume_302").as_deref(),
+            Some("/sang-tac/1234-thien-than-nha-ben/t302-tap-02?page=2"),
+            "{}",
+            name
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use docln_fetch::crawler::RobotsRules;
+use std::time::Duration;
+
+const ROBOTS_TXT: &str = "\
+# docln robots
+User-agent: BadBot
+Disallow: /
+
+User-agent: *
+Disallow: /admin
+Disallow: /*?sort=
+Allow: /admin/public
+Disallow: /search$
+Crawl-delay: 2.5
+";
+
+#[test]
+fn applies_wildcard_group_rules() {
+    let robots = RobotsRules::parse(ROBOTS_TXT, "Mozilla/5.0 docln-fetch");
+
+    assert!(robots.is_allowed("/sang-tac/1234"));
+    assert!(!robots.is_allowed("/admin/users"));
+    assert!(robots.is_allowed("/admin/public/page"));
+    assert!(!robots.is_allowed("/danh-sach?sort=new"));
+    assert!(!robots.is_allowed("/search"));
+    assert!(robots.is_allowed("/search/novel"));
+    assert!(!robots.is_url_allowed("https://docln.net/admin"));
+    assert_eq!(robots.crawl_delay(), Some(Duration::from_millis(2500)));
+}
+
+#[test]
+fn prefers_group_matching_user_agent() {
+    let robots = RobotsRules::parse(ROBOTS_TXT, "BadBot/1.0");
+    assert!(!robots.is_allowed("/sang-tac/1234"));
+    assert_eq!(robots.crawl_delay(), None);
+
+    assert!(RobotsRules::parse("", "BadBot/1.0").is_allowed("/"));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::fixture;
+use docln_fetch::crawler::{ContentHashes, CrawlState, NovelParser, RemovedChapters};
+use docln_fetch::{Chapter, Epub, Volume};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn chapter(title: &str, url: &str) -> Chapter {
+    Chapter::builder().title(title.to_string()).url(url.to_string()).build()
+}
+
+fn novel(urls: &[&str]) -> Epub {
+    let mut epub = NovelParser.parse_novel_info(&fixture("series_page.html"), "https://docln.net/sang-tac/1234", 1234).unwrap();
+    let chapters = urls.iter().enumerate().map(|(index, url)| chapter(&format!("Chương {}", index + 1), url)).collect();
+    epub.volumes.push(Volume::builder().title("Tập 1".to_string()).chapters(chapters).build());
+    epub
+}
+
+/// 为每个章节写入XHTML并记录路径，模拟一次完成的爬取
+fn stage(epub: &mut Epub, oebps_dir: &Path) {
+    for (chapter_index, chapter) in epub.volumes[0].chapters.iter_mut().enumerate() {
+        let xhtml_path = format!("text/volume_001/chapter_{:03}.xhtml", chapter_index + 1);
+        let path = oebps_dir.join(&xhtml_path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("<p>{}</p>", chapter.url)).unwrap();
+        chapter.xhtml_path = Some(xhtml_path);
+    }
+}
+
+fn temp_oebps(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("docln_fetch_state_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn reuses_unchanged_chapters_and_fetches_new_ones() {
+    let oebps_dir = temp_oebps("append");
+    let mut previous = novel(&["/c/1", "/c/2"]);
+    stage(&mut previous, &oebps_dir);
+    let state = CrawlState::from_epub(&previous, &oebps_dir);
+    assert_eq!(state.chapters.len(), 2);
+
+    // 末尾新增一章，第二章的标题有变化
+    let mut current = novel(&["/c/1", "/c/2", "/c/3"]);
+    current.volumes[0].chapters[1].title = "Chương 2 (sửa)".to_string();
+    let plan = state.plan(&mut current, &oebps_dir, RemovedChapters::Keep);
+
+    assert_eq!(plan.unchanged, vec![[0].into_iter().collect()]);
+    assert_eq!((plan.added, plan.changed, plan.removed), (1, 1, 0));
+
+    // 本地文件被修改后不再复用
+    fs::write(oebps_dir.join("text/volume_001/chapter_001.xhtml"), "<p>khác</p>").unwrap();
+    let plan = state.plan(&mut novel(&["/c/1", "/c/2"]), &oebps_dir, RemovedChapters::Keep);
+    assert!(plan.unchanged[0].contains(&1) && !plan.unchanged[0].contains(&0));
+    fs::remove_dir_all(oebps_dir).unwrap();
+}
+
+#[test]
+fn keeps_or_drops_removed_chapters() {
+    let oebps_dir = temp_oebps("removed");
+    let mut previous = novel(&["/c/1", "/c/2", "/c/3"]);
+    stage(&mut previous, &oebps_dir);
+    let state = CrawlState::from_epub(&previous, &oebps_dir);
+
+    let mut kept = novel(&["/c/1", "/c/2"]);
+    let plan = state.plan(&mut kept, &oebps_dir, RemovedChapters::Keep);
+    assert_eq!(kept.volumes[0].chapters.iter().map(|chapter| chapter.url.as_str()).collect::<Vec<_>>(), ["/c/1", "/c/2", "/c/3"]);
+    assert_eq!(plan.unchanged[0].len(), 3);
+    assert_eq!(plan.removed, 1);
+
+    let mut dropped = novel(&["/c/1", "/c/2"]);
+    let plan = state.plan(&mut dropped, &oebps_dir, RemovedChapters::Drop);
+    assert_eq!(dropped.volumes[0].chapters.len(), 2);
+    assert_eq!(plan.unchanged[0].len(), 2);
+    assert!(!oebps_dir.join("text/volume_001/chapter_003.xhtml").exists());
+    fs::remove_dir_all(oebps_dir).unwrap();
+}
+
+#[test]
+fn content_hashes_survive_reload() {
+    let path = std::env::temp_dir().join(format!("docln_fetch_test_content_hashes_{}.json", std::process::id()));
+    let content_hashes = ContentHashes::load(&path);
+    assert_eq!(content_hashes.get("https://docln.net/truyen/1234/c1"), None);
+
+    content_hashes.insert("https://docln.net/truyen/1234/c1", "abc123".to_string());
+    content_hashes.save().unwrap();
+
+    let reloaded = ContentHashes::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(reloaded.get("https://docln.net/truyen/1234/c1").as_deref(), Some("abc123"));
+}